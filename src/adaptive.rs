@@ -0,0 +1,215 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::board;
+use crate::difficulty::{Persona, PERSONAS};
+use crate::game::{GameState, GameStatus};
+use crate::review::{self, MoveReview, Verdict};
+
+const HEADER: &str = "# connect_4 adaptive difficulty: each player's level, from 0 (beginner) to 4 (perfect)";
+
+/** Where a new player starts (casual) */
+pub const START_LEVEL: f32 = 1.0;
+/** The strongest level (perfect) */
+pub const MAX_LEVEL: f32 = (PERSONAS.len() - 1) as f32;
+
+// how far one game's result moves the level: a win is +RESULT_STEP, a loss -RESULT_STEP
+const RESULT_STEP: f32 = 0.4;
+// how far move quality moves the level, per unit of accuracy above or below EXPECTED_ACCURACY
+const ACCURACY_STEP: f32 = 0.5;
+const EXPECTED_ACCURACY: f32 = 0.8;
+// search depth used to grade the player's moves after each game
+const REVIEW_DEPTH: u16 = 4;
+
+/**
+ Every player's adaptive level, kept between sessions <br/>
+ Levels go up as a player wins (and plays accurately) and down as they lose, aiming for games they win about half the time
+ */
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SkillStore {
+    // stored rounded to two places, so the file stays readable
+    levels: BTreeMap<String, f64>,
+}
+
+impl SkillStore {
+    /**
+     Where levels are kept for a config file: `skill.toml` in the same directory
+     * `config_path` - the config file
+     */
+    pub fn path_for(config_path: &Path) -> PathBuf { config_path.with_file_name("skill.toml") }
+
+    /**
+     Reads the levels, starting fresh if the file doesn't exist yet
+     * `path` - the file to read
+     */
+    pub fn load(path: &Path) -> Result<SkillStore, String> {
+        let text: String = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(SkillStore::default()),
+            Err(err) => return Err(format!("couldn't read {}: {}", path.display(), err)),
+        };
+
+        return toml::from_str(&text).map_err(|err| format!("{}: {}", path.display(), err.message()));
+    }
+
+    /**
+     Writes the levels, creating the directory if needed
+     * `path` - the file to write
+     */
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() { std::fs::create_dir_all(dir)?; }
+
+        let text: String = toml::to_string(self).map_err(io::Error::other)?;
+        return std::fs::write(path, format!("{}\n\n{}", HEADER, text));
+    }
+
+    /**
+     A player's current level (`START_LEVEL` if they haven't played an adaptive game yet)
+     * `player` - the player's name
+     */
+    pub fn level(&self, player: &str) -> f32 { self.levels.get(player).map_or(START_LEVEL, |&level| level as f32) }
+
+    /**
+     Moves a player's level after a game, returning the new level
+     * `player` - the player's name
+     * `result` - 1 for a win, 0.5 for a draw, 0 for a loss (from the player's side)
+     * `accuracy` - the share of the player's moves that didn't throw away a result (see `accuracy`)
+     */
+    pub fn record_game(&mut self, player: &str, result: f32, accuracy: f32) -> f32 {
+        let change: f32 = RESULT_STEP * (result - 0.5) * 2.0 + ACCURACY_STEP * (accuracy - EXPECTED_ACCURACY);
+        let level: f32 = (self.level(player) + change).clamp(0.0, MAX_LEVEL);
+
+        self.levels.insert(player.to_string(), (level as f64 * 100.0).round() / 100.0);
+        return self.level(player);
+    }
+}
+
+/**
+ The opponent for a level: whole levels are the personas (0 = beginner ... 4 = perfect), and levels in between blend the two either side
+ * `level` - from 0 to `MAX_LEVEL`
+ */
+pub fn persona_at(level: f32) -> Persona {
+    let level: f32 = level.clamp(0.0, MAX_LEVEL);
+    let lower: Persona = PERSONAS[level.floor() as usize];
+    let upper: Persona = PERSONAS[(level.ceil() as usize).min(PERSONAS.len() - 1)];
+    let blend: f32 = level.fract();
+    let mix = |low: f32, high: f32| low + (high - low) * blend;
+
+    return Persona {
+        name: "adaptive",
+        depth: mix(lower.depth as f32, upper.depth as f32).round() as u16,
+        temperature: mix(lower.temperature, upper.temperature),
+        missed_block: mix(lower.missed_block, upper.missed_block),
+        description: "matches your level, getting stronger as you win",
+    };
+}
+
+/**
+ A level as shown on screen, e.g. `1.8, between casual and club` or `2.0, club`
+ * `level` - from 0 to `MAX_LEVEL`
+ */
+pub fn describe(level: f32) -> String {
+    // named after the level as it's printed, so 1.97 reads as "2.0, club"
+    let level: f32 = ((level * 10.0).round() / 10.0).clamp(0.0, MAX_LEVEL);
+    let lower: &str = PERSONAS[level.floor() as usize].name;
+    let upper: &str = PERSONAS[(level.ceil() as usize).min(PERSONAS.len() - 1)].name;
+
+    if lower == upper { return format!("{:.1}, {}", level, lower); }
+
+    return format!("{:.1}, between {} and {}", level, lower, upper);
+}
+
+/**
+ Red's result in a finished game, the way `SkillStore::record_game` takes it: 1 a win, 0.5 a draw, 0 a loss
+ * `game` - the finished game
+ */
+pub fn red_result(game: &GameState) -> f32 {
+    return match game.status() {
+        GameStatus::Won(piece) if piece == board::RED_PIECE => 1.0,
+        GameStatus::Won(_) => 0.0,
+        _ => 0.5,
+    };
+}
+
+/**
+ The share of a player's moves that the engine doesn't rate as a blunder or a missed win (1 if they made no moves)
+ * `moves` - the columns played in the game, in order
+ * `piece` - the player's piece
+ */
+pub fn accuracy(moves: &[usize], piece: char) -> f32 {
+    let reviews: Vec<MoveReview> = review::review_game(moves, REVIEW_DEPTH);
+    let own: Vec<&MoveReview> = reviews.iter().filter(|move_review| move_review.piece == piece).collect();
+
+    if own.is_empty() { return 1.0; }
+
+    let sound: usize = own.iter().filter(|move_review| matches!(move_review.verdict, Verdict::Best | Verdict::Inaccuracy)).count();
+    return sound as f32 / own.len() as f32;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::{BEGINNER, CASUAL, CLUB, PERFECT};
+
+    #[test]
+    fn wins_raise_the_level_and_losses_lower_it() {
+        let mut store: SkillStore = SkillStore::default();
+        assert_eq!(store.level("Ann"), START_LEVEL);
+
+        let after_win: f32 = store.record_game("Ann", 1.0, EXPECTED_ACCURACY);
+        assert!(after_win > START_LEVEL);
+
+        let after_loss: f32 = store.record_game("Ann", 0.0, EXPECTED_ACCURACY);
+        assert!(after_loss < after_win);
+
+        // sloppy play costs a little even in a draw, and other players are untouched
+        assert!(store.record_game("Ann", 0.5, 0.2) < after_loss);
+        assert_eq!(store.level("Bob"), START_LEVEL);
+    }
+
+    #[test]
+    fn levels_stay_within_the_personas() {
+        let mut store: SkillStore = SkillStore::default();
+
+        for _ in 0..20 { store.record_game("Ann", 1.0, 1.0); }
+        assert_eq!(store.level("Ann"), MAX_LEVEL);
+
+        for _ in 0..20 { store.record_game("Ann", 0.0, 0.0); }
+        assert_eq!(store.level("Ann"), 0.0);
+
+        assert_eq!(persona_at(-1.0).depth, BEGINNER.depth);
+        assert_eq!(persona_at(MAX_LEVEL + 1.0).depth, PERFECT.depth);
+    }
+
+    #[test]
+    fn levels_between_personas_blend_them() {
+        let halfway: Persona = persona_at(1.5);
+
+        assert_eq!(halfway.depth, (CASUAL.depth + CLUB.depth) / 2);
+        assert_eq!(halfway.temperature, (CASUAL.temperature + CLUB.temperature) / 2.0);
+        assert_eq!(persona_at(2.0).missed_block, CLUB.missed_block);
+
+        assert_eq!(describe(1.97), "2.0, club");
+        assert_eq!(describe(1.5), "1.5, between casual and club");
+    }
+
+    #[test]
+    fn the_store_survives_saving_and_loading() {
+        let path: PathBuf = std::env::temp_dir().join(format!("connect_4_skill_{}", std::process::id())).join("skill.toml");
+        assert_eq!(SkillStore::load(&path).unwrap(), SkillStore::default());
+
+        let mut store: SkillStore = SkillStore::default();
+        store.record_game("Ann", 1.0, 0.9);
+        store.record_game("Bob", 0.0, 0.5);
+        store.save(&path).unwrap();
+
+        let loaded: SkillStore = SkillStore::load(&path).unwrap();
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert_eq!(loaded, store);
+        assert_eq!(loaded.level("Ann"), 1.45);
+    }
+}
@@ -1,8 +1,91 @@
-#[path ="board.rs"]
-mod board;
+use std::cell::Cell;
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::board;
+use crate::eval::Evaluator;
 
 const NO_COL: usize = 8;
 
+thread_local! {
+    // positions visited by `minimax` on this thread since the last reset
+    static NODES_SEARCHED: Cell<u64> = const { Cell::new(0) };
+}
+
+/** How many positions `minimax` has visited on this thread since `reset_nodes_searched` */
+pub fn nodes_searched() -> u64 { NODES_SEARCHED.with(|nodes| nodes.get()) }
+
+/** Starts a new count for `nodes_searched` */
+pub fn reset_nodes_searched() { NODES_SEARCHED.with(|nodes| nodes.set(0)); }
+
+/**
+ How `minimax` picks the column it starts from, which is the one it plays when no column does better (every move loses) <br/>
+ Written as a number or `deterministic`, e.g. `seed: 42` in a saved game
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TieBreak {
+    // random columns from a generator started with this seed, so the same seed always picks the same columns
+    Seeded(u64),
+    // always the open column nearest the centre
+    Deterministic,
+}
+
+impl TieBreak {
+    /** A new random seed */
+    pub fn random() -> TieBreak { TieBreak::Seeded(fastrand::u64(..)) }
+
+    /**
+     Reads a tie-break from its text form
+     * `text` - a seed, or `deterministic`
+     */
+    pub fn parse(text: &str) -> Option<TieBreak> {
+        if text.trim().eq_ignore_ascii_case("deterministic") { return Some(TieBreak::Deterministic); }
+
+        return text.trim().parse::<u64>().ok().map(TieBreak::Seeded);
+    }
+}
+
+impl fmt::Display for TieBreak {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TieBreak::Seeded(seed) => write!(f, "{}", seed),
+            TieBreak::Deterministic => write!(f, "deterministic"),
+        }
+    }
+}
+
+/** Picks starting columns for `minimax` the way a `TieBreak` says to */
+pub struct TieBreaker {
+    // `None` in deterministic mode
+    rng: Option<fastrand::Rng>,
+}
+
+impl TieBreaker {
+    /**
+     Creates a tie-breaker, starting its generator from the seed (if there is one)
+     * `tie_break` - how to pick
+     */
+    pub fn new(tie_break: TieBreak) -> TieBreaker {
+        return match tie_break {
+            TieBreak::Seeded(seed) => TieBreaker { rng: Some(fastrand::Rng::with_seed(seed)) },
+            TieBreak::Deterministic => TieBreaker { rng: None },
+        };
+    }
+
+    /**
+     Picks one of the open columns
+     * `open_columns` - the columns to pick from (never empty)
+     */
+    fn pick(&mut self, open_columns: &[usize]) -> usize {
+        return match &mut self.rng {
+            Some(rng) => open_columns[rng.usize(..open_columns.len())],
+            None => *open_columns.iter().min_by_key(|&&col| col.abs_diff(board::BOARD_WIDTH / 2)).expect("no open columns left!"),
+        };
+    }
+}
+
 /**
  A recursive operation that retrieves the best possible move considering all possible future moves (up to a certain depth)
  * `playing_board` - the board currently being used in the game
@@ -10,8 +93,12 @@ const NO_COL: usize = 8;
  * `is_max` - are we maximizing the AI?
  * `alpha` - alpha flag
  * `beta` - beta flag
+ * `tie_breaker` - picks the column each level starts from
+ * `evaluator` - how positions are scored once the search stops
  */
-pub fn minimax(playing_board: &mut [char; board::BOARD_SIZE], depth: u16, is_max: bool, mut alpha: i16, mut beta: i16) -> (usize, i16) {
+pub fn minimax(playing_board: &mut [char; board::BOARD_SIZE], depth: u16, is_max: bool, mut alpha: i16, mut beta: i16, tie_breaker: &mut TieBreaker, evaluator: &Evaluator) -> (usize, i16) {
+    NODES_SEARCHED.with(|nodes| nodes.set(nodes.get() + 1));
+
     let open_columns: Vec<usize> = board::get_open_columns(playing_board);
 
     // .0 = player won
@@ -31,22 +118,22 @@ pub fn minimax(playing_board: &mut [char; board::BOARD_SIZE], depth: u16, is_max
         }
         // Reached end of depth
         else {
-            return (NO_COL, board::evaluate_board(playing_board, board::YELLOW_PIECE));
+            return (NO_COL, evaluator.evaluate(playing_board, board::YELLOW_PIECE));
         }
     }
 
     // Maximizing the AI
     if is_max {
         let mut eval: i16 = i16::MIN;
-        // fetching an initial random column
-        let mut column: usize = open_columns[fastrand::usize(..open_columns.len())];
+        // fetching an initial column from the tie-breaker
+        let mut column: usize = tie_breaker.pick(&open_columns);
 
         for col in open_columns {
             // Make initial move
             let temp_move: (usize, usize) = board::drop_at_column(playing_board, col, board::YELLOW_PIECE);
 
             // Evaluate said move
-            let new_eval: (usize, i16) = minimax(playing_board, depth - 1, false, alpha, beta);
+            let new_eval: (usize, i16) = minimax(playing_board, depth - 1, false, alpha, beta, tie_breaker, evaluator);
 
             // Undo previous move
             board::set_square_at(playing_board, temp_move.0, temp_move.1, board::EMPTY);
@@ -67,15 +154,15 @@ pub fn minimax(playing_board: &mut [char; board::BOARD_SIZE], depth: u16, is_max
     // Minimizing the player
     else {
         let mut eval: i16 = i16::MAX;
-        // fetching an initial random column
-        let mut column: usize = open_columns[fastrand::usize(..open_columns.len())];
+        // fetching an initial column from the tie-breaker
+        let mut column: usize = tie_breaker.pick(&open_columns);
 
         for col in open_columns {
             // Make initial move
             let temp_move: (usize, usize) = board::drop_at_column(playing_board, col, board::RED_PIECE);
 
             // Evaluate said move
-            let new_eval: (usize, i16) = minimax(playing_board, depth - 1, true, alpha, beta);
+            let new_eval: (usize, i16) = minimax(playing_board, depth - 1, true, alpha, beta, tie_breaker, evaluator);
 
             // Undo previous move
             board::set_square_at(playing_board, temp_move.0, temp_move.1, board::EMPTY);
@@ -103,5 +190,149 @@ pub fn minimax(playing_board: &mut [char; board::BOARD_SIZE], depth: u16, is_max
 fn is_terminal_node(playing_board: &[char; board::BOARD_SIZE]) -> (bool, bool, bool) {
     return (board::is_winning_board(playing_board, board::RED_PIECE), 
             board::is_winning_board(playing_board, board::YELLOW_PIECE), 
-            board::get_open_columns(playing_board).len() == 0);
-}
\ No newline at end of file
+            board::get_open_columns(playing_board).is_empty());
+}
+
+/**
+ Scores every column for the piece about to move, from that piece's point of view (higher is better) <br/>
+ Filled columns are `None`. A guaranteed win within the search is `i16::MAX`, a guaranteed loss `i16::MIN`
+ * `playing_board` - the board currently being used in the game
+ * `depth` - how far the search should go, counting the move being scored
+ * `piece` - the piece about to move
+ */
+pub fn score_columns(playing_board: &[char; board::BOARD_SIZE], depth: u16, piece: char) -> [Option<i16>; board::BOARD_WIDTH] {
+    return score_columns_with(playing_board, depth, piece, &Evaluator::default());
+}
+
+/**
+ Scores every column like `score_columns`, judging positions a given way (e.g. an AI player's style or evaluator)
+ * `playing_board` - the board currently being used in the game
+ * `depth` - how far the search should go, counting the move being scored
+ * `piece` - the piece about to move
+ * `evaluator` - how positions are scored once the search stops
+ */
+pub fn score_columns_with(playing_board: &[char; board::BOARD_SIZE], depth: u16, piece: char, evaluator: &Evaluator) -> [Option<i16>; board::BOARD_WIDTH] {
+    let mut scores: [Option<i16>; board::BOARD_WIDTH] = [None; board::BOARD_WIDTH];
+    let mut scratch_board: [char; board::BOARD_SIZE] = *playing_board;
+    // only the scores are used, so which column each level starts from doesn't matter
+    let mut tie_breaker: TieBreaker = TieBreaker::new(TieBreak::Deterministic);
+
+    for col in board::get_open_columns(playing_board) {
+        let temp_move: (usize, usize) = board::drop_at_column(&mut scratch_board, col, piece);
+
+        // minimax always scores from the AI's (yellow's) side, so flip it for red
+        let eval: (usize, i16) = minimax(&mut scratch_board, depth.max(1) - 1, piece == board::RED_PIECE, i16::MIN, i16::MAX, &mut tie_breaker, evaluator);
+        scores[col] = Some(if piece == board::YELLOW_PIECE { eval.1 } else { flip_score(eval.1) });
+
+        board::set_square_at(&mut scratch_board, temp_move.0, temp_move.1, board::EMPTY);
+    }
+
+    return scores;
+}
+
+/**
+ Turns a score from yellow's side into red's side, keeping forced wins and losses at the extremes
+ * `score` - score from yellow's side
+ */
+pub fn flip_score(score: i16) -> i16 {
+    return match score {
+        i16::MAX => i16::MIN,
+        i16::MIN => i16::MAX,
+        _ => -score,
+    };
+}
+
+/**
+ Picks the highest scoring column out of `score_columns`, preferring columns nearer the centre on ties
+ * `scores` - per-column scores
+ */
+pub fn best_scored_column(scores: &[Option<i16>; board::BOARD_WIDTH]) -> Option<usize> {
+    let center: usize = board::BOARD_WIDTH / 2;
+
+    return (0..board::BOARD_WIDTH)
+        .filter(|&col| scores[col].is_some())
+        .max_by_key(|&col| (scores[col], std::cmp::Reverse(col.abs_diff(center))));
+}
+
+/** Why `suggest_move` recommended a column */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HintReason {
+    // dropping here connects four immediately
+    WinNow,
+    // the opponent would connect four here on their next move
+    Block,
+    // best minimax score found at the hint depth (from the hinted piece's side)
+    BestScore(i16),
+}
+
+/**
+ Recommends a column for any piece, running `minimax` from that piece's side (red is the minimizing side)
+ * `playing_board` - the board currently being used in the game
+ * `depth` - how far the search should go
+ * `piece` - the piece to recommend a move for
+ */
+pub fn suggest_move(playing_board: &[char; board::BOARD_SIZE], depth: u16, piece: char) -> (usize, HintReason) {
+    let opp_piece: char = if piece == board::RED_PIECE { board::YELLOW_PIECE } else { board::RED_PIECE };
+    let mut scratch_board: [char; board::BOARD_SIZE] = *playing_board;
+
+    // immediate wins come first, then immediate blocks
+    for (check_piece, reason) in [(piece, HintReason::WinNow), (opp_piece, HintReason::Block)] {
+        for col in board::get_open_columns(playing_board) {
+            let temp_move: (usize, usize) = board::drop_at_column(&mut scratch_board, col, check_piece);
+            let wins: bool = board::is_winning_board(&scratch_board, check_piece);
+            board::set_square_at(&mut scratch_board, temp_move.0, temp_move.1, board::EMPTY);
+
+            if wins { return (col, reason); }
+        }
+    }
+
+    // hints stay the same however often they're asked for
+    let best: (usize, i16) = minimax(&mut scratch_board, depth.max(1), piece == board::YELLOW_PIECE, i16::MIN, i16::MAX, &mut TieBreaker::new(TieBreak::Deterministic), &Evaluator::default());
+    let score: i16 = if piece == board::YELLOW_PIECE { best.1 } else { flip_score(best.1) };
+
+    return (best.0, HintReason::BestScore(score));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameState;
+
+    fn board_after(moves: &[usize]) -> [char; board::BOARD_SIZE] {
+        let mut game: GameState = GameState::new();
+        for &col in moves { game.play(col).unwrap(); }
+
+        return *game.board();
+    }
+
+    #[test]
+    fn forced_wins_for_red_score_the_maximum() {
+        // red's C and D along the bottom become two threats with B or E
+        let playing_board: [char; board::BOARD_SIZE] = board_after(&[2, 6, 3, 6]);
+        let scores: [Option<i16>; board::BOARD_WIDTH] = score_columns(&playing_board, 3, board::RED_PIECE);
+
+        assert_eq!((scores[1], scores[4]), (Some(i16::MAX), Some(i16::MAX)));
+        assert_eq!(suggest_move(&playing_board, 3, board::RED_PIECE), (1, HintReason::BestScore(i16::MAX)));
+    }
+
+    #[test]
+    fn forced_losses_for_red_score_the_minimum() {
+        // yellow has B, C and D along the bottom, open at both ends
+        let scores: [Option<i16>; board::BOARD_WIDTH] = score_columns(&board_after(&[6, 1, 6, 2, 5, 3]), 2, board::RED_PIECE);
+        assert!(scores.iter().all(|&score| score == Some(i16::MIN)));
+
+        // nothing to block yet, but yellow wins within four moves whatever red does
+        let (_, reason) = suggest_move(&board_after(&[2, 2, 1, 3, 5, 1, 1, 3, 3, 2]), 4, board::RED_PIECE);
+        assert_eq!(reason, HintReason::BestScore(i16::MIN));
+    }
+
+    #[test]
+    fn hints_take_a_win_before_blocking_one() {
+        // red has A, B and C along the bottom and yellow is stacking up G
+        assert_eq!(suggest_move(&board_after(&[0, 6, 1, 6, 2, 5]), 1, board::RED_PIECE), (3, HintReason::WinNow));
+        assert_eq!(suggest_move(&board_after(&[0, 6, 1, 6, 2]), 1, board::YELLOW_PIECE), (3, HintReason::Block));
+
+        // yellow's three in G can wait: red connects four first
+        assert_eq!(suggest_move(&board_after(&[0, 6, 1, 6, 2, 6]), 1, board::RED_PIECE), (3, HintReason::WinNow));
+    }
+}
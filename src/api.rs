@@ -0,0 +1,309 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ai_opponent;
+use crate::board;
+use crate::game::{GameState, GameStatus};
+
+const DEFAULT_ANALYSIS_DEPTH: u16 = 5;
+const MAX_DEPTH: u16 = 9;
+// positions are tiny, so anything bigger than this is not a real request
+const MAX_BODY_BYTES: usize = 64 * 1024;
+// how long a client can go quiet before its connection is dropped, so a short body can't hold a thread forever
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+// most of a refused body read (and thrown away) before closing, so the client sees the response instead of a reset
+const MAX_DRAIN_BYTES: u64 = 1024 * 1024;
+
+/** Body of `POST /analyze`: the moves played so far (0 = A) and an optional search depth */
+#[derive(Deserialize)]
+struct AnalyzeRequest {
+    moves: Vec<usize>,
+    depth: Option<u16>,
+}
+
+/** Body of `POST /move`: the moves played so far (0 = A) and the AI's search depth (1 - 9) */
+#[derive(Deserialize)]
+struct MoveRequest {
+    moves: Vec<usize>,
+    difficulty: u16,
+}
+
+#[derive(Serialize)]
+struct AnalyzeResponse {
+    turn: char,
+    depth: u16,
+    scores: [Option<i16>; board::BOARD_WIDTH],
+    best: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct MoveResponse {
+    column: usize,
+    score: i16,
+}
+
+#[derive(Serialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+/**
+ Handles a single API request without any networking, returning the status code and JSON body <br/>
+ This is everything the server does per request, so it can be called directly from scripts or tests
+ * `method` - HTTP method, e.g. `POST`
+ * `path` - request path, e.g. `/analyze`
+ * `body` - request body (JSON)
+ */
+pub fn route(method: &str, path: &str, body: &str) -> (u16, String) {
+    let result: Result<String, (u16, String)> = match (method, path) {
+        ("POST", "/analyze") => analyze(body),
+        ("POST", "/move") => ai_move(body),
+        (_, "/analyze") | (_, "/move") => Err((405, String::from("use POST"))),
+        _ => Err((404, format!("no endpoint at {}", path))),
+    };
+
+    return match result {
+        Ok(json) => (200, json),
+        Err((status, error)) => (status, error_body(error)),
+    };
+}
+
+fn error_body(error: String) -> String { serde_json::to_string(&ErrorResponse { error }).expect("errors always serialize") }
+
+fn analyze(body: &str) -> Result<String, (u16, String)> {
+    let request: AnalyzeRequest = serde_json::from_str(body).map_err(|err| (400, err.to_string()))?;
+    let depth: u16 = request.depth.unwrap_or(DEFAULT_ANALYSIS_DEPTH);
+    check_depth(depth)?;
+
+    let game: GameState = replay(&request.moves)?;
+    let scores: [Option<i16>; board::BOARD_WIDTH] = ai_opponent::score_columns(game.board(), depth, game.current_piece());
+
+    let response: AnalyzeResponse = AnalyzeResponse {
+        turn: game.current_piece(),
+        depth,
+        scores,
+        best: ai_opponent::best_scored_column(&scores),
+    };
+
+    return Ok(serde_json::to_string(&response).expect("responses always serialize"));
+}
+
+fn ai_move(body: &str) -> Result<String, (u16, String)> {
+    let request: MoveRequest = serde_json::from_str(body).map_err(|err| (400, err.to_string()))?;
+    check_depth(request.difficulty)?;
+
+    let game: GameState = replay(&request.moves)?;
+    let scores: [Option<i16>; board::BOARD_WIDTH] = ai_opponent::score_columns(game.board(), request.difficulty, game.current_piece());
+    let column: usize = ai_opponent::best_scored_column(&scores).expect("an unfinished game always has an open column");
+
+    let response: MoveResponse = MoveResponse { column, score: scores[column].unwrap_or_default() };
+
+    return Ok(serde_json::to_string(&response).expect("responses always serialize"));
+}
+
+fn check_depth(depth: u16) -> Result<(), (u16, String)> {
+    if (1..=MAX_DEPTH).contains(&depth) { return Ok(()); }
+
+    return Err((400, format!("depth must be between 1 and {}", MAX_DEPTH)));
+}
+
+/**
+ Rebuilds a position from its move list through the game rules, rejecting illegal or finished games
+ * `moves` - columns played so far (0 = A)
+ */
+fn replay(moves: &[usize]) -> Result<GameState, (u16, String)> {
+    let mut game: GameState = GameState::new();
+
+    for (i, &col) in moves.iter().enumerate() {
+        game.play(col).map_err(|err| (400, format!("move {} is illegal: {}", i + 1, err)))?;
+    }
+
+    if game.status() != GameStatus::InProgress {
+        return Err((400, String::from("the game is already over")));
+    }
+
+    return Ok(game);
+}
+
+/**
+ Serves the API over HTTP on the given address, one thread per connection. Only returns if binding fails
+ * `addr` - address to listen on, e.g. `127.0.0.1:8080`
+ */
+pub fn serve(addr: &str) -> io::Result<()> {
+    let listener: TcpListener = TcpListener::bind(addr)?;
+
+    println!("API listening on http://{}", listener.local_addr()?);
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => { thread::spawn(move || if let Err(err) = handle_connection(stream) { eprintln!("request failed: {}", err); }); },
+            Err(err) => eprintln!("connection failed: {}", err),
+        }
+    }
+
+    return Ok(());
+}
+
+/**
+ Reads one HTTP/1.1 request, answers it via `route`, then closes the connection
+ * `stream` - the client's connection
+ */
+fn handle_connection(stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let mut reader: BufReader<TcpStream> = BufReader::new(stream.try_clone()?);
+
+    let mut request_line: String = String::new();
+    reader.read_line(&mut request_line)?;
+
+    let mut parts = request_line.split_whitespace();
+    let method: String = parts.next().unwrap_or_default().to_string();
+    let path: String = parts.next().unwrap_or_default().to_string();
+
+    // headers; only the body length matters here
+    let mut content_length: Result<usize, String> = Ok(0);
+    loop {
+        let mut header: String = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() { break; }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse::<usize>().map_err(|_| format!("Content-Length must be a number, not {:?}", value.trim()));
+            }
+        }
+    }
+
+    // the status and JSON to answer with, and whether the body was refused without being read
+    let (status, json, refused) = match content_length {
+        Err(error) => (400, error_body(error), true),
+        Ok(length) if length > MAX_BODY_BYTES => (413, error_body(format!("request bodies are limited to {} bytes", MAX_BODY_BYTES)), true),
+        Ok(length) => {
+            let mut body: Vec<u8> = vec![0; length];
+            reader.read_exact(&mut body)?;
+
+            let (status, json) = route(&method, &path, &String::from_utf8_lossy(&body));
+            (status, json, false)
+        },
+    };
+    let reason: &str = match status { 200 => "OK", 400 => "Bad Request", 404 => "Not Found", 405 => "Method Not Allowed", 413 => "Payload Too Large", _ => "Error" };
+
+    let mut writer: TcpStream = stream;
+    write!(writer, "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", status, reason, json.len(), json)?;
+    writer.flush()?;
+
+    // a refused body is still on its way; closing with it unread would reset the connection and could lose the response
+    if refused {
+        writer.shutdown(Shutdown::Write)?;
+        let _ = io::copy(&mut reader.take(MAX_DRAIN_BYTES), &mut io::sink());
+    }
+
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn post(path: &str, body: &str) -> (u16, Value) {
+        let (status, json) = route("POST", path, body);
+
+        return (status, serde_json::from_str(&json).unwrap());
+    }
+
+    #[test]
+    fn analyze_scores_every_open_column() {
+        // column A is full
+        let (status, json) = post("/analyze", r#"{"moves": [0, 0, 0, 0, 0, 0], "depth": 2}"#);
+
+        assert_eq!(status, 200);
+        assert_eq!(json["turn"], "R");
+        assert_eq!(json["depth"], 2);
+        assert_eq!(json["scores"][0], Value::Null);
+        assert!((1..board::BOARD_WIDTH).all(|col| json["scores"][col].is_i64()));
+    }
+
+    #[test]
+    fn move_blocks_a_four() {
+        // red has A, B and C along the bottom
+        let (status, json) = post("/move", r#"{"moves": [0, 6, 1, 6, 2], "difficulty": 3}"#);
+
+        assert_eq!(status, 200);
+        assert_eq!(json["column"], 3);
+    }
+
+    #[test]
+    fn illegal_move_lists_are_rejected() {
+        let (status, json) = post("/analyze", r#"{"moves": [0, 0, 0, 0, 0, 0, 0]}"#);
+        assert_eq!(status, 400);
+        assert!(json["error"].as_str().unwrap().starts_with("move 7 is illegal"));
+
+        let (status, _) = post("/move", r#"{"moves": [7], "difficulty": 3}"#);
+        assert_eq!(status, 400);
+
+        // red connects four down column A
+        let (status, json) = post("/move", r#"{"moves": [0, 1, 0, 1, 0, 1, 0], "difficulty": 3}"#);
+        assert_eq!(status, 400);
+        assert_eq!(json["error"], "the game is already over");
+    }
+
+    #[test]
+    fn bad_requests_get_error_statuses() {
+        assert_eq!(post("/analyze", "not json").0, 400);
+        assert_eq!(post("/move", r#"{"moves": [], "difficulty": 0}"#).0, 400);
+        assert_eq!(post("/analyze", r#"{"moves": [], "depth": 10}"#).0, 400);
+
+        assert_eq!(route("POST", "/nowhere", "{}").0, 404);
+        assert_eq!(route("GET", "/analyze", "").0, 405);
+        assert_eq!(route("PUT", "/move", "{}").0, 405);
+    }
+
+    /** Sends a raw request to `handle_connection` over a real socket and returns everything it answers */
+    fn exchange(request: Vec<u8>) -> String {
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client: TcpStream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let server = thread::spawn(move || handle_connection(listener.accept().unwrap().0));
+
+        // written from its own thread, since a big body only gets through while the server is reading
+        let mut sender: TcpStream = client.try_clone().unwrap();
+        let sending = thread::spawn(move || sender.write_all(&request));
+
+        let mut response: String = String::new();
+        client.read_to_string(&mut response).unwrap();
+        sending.join().unwrap().unwrap();
+        drop(client);
+        server.join().unwrap().unwrap();
+
+        return response;
+    }
+
+    #[test]
+    fn requests_are_answered_over_http() {
+        let body: &str = r#"{"moves": [], "depth": 1}"#;
+        let response: String = exchange(format!("POST /analyze HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}", body.len(), body).into_bytes());
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+        assert!(response.ends_with("\"best\":3}"), "{}", response);
+    }
+
+    #[test]
+    fn oversized_bodies_are_refused() {
+        let mut request: Vec<u8> = format!("POST /analyze HTTP/1.1\r\nContent-Length: {}\r\n\r\n", MAX_BODY_BYTES + 1).into_bytes();
+        request.extend(vec![b' '; MAX_BODY_BYTES + 1]);
+
+        let response: String = exchange(request);
+        assert!(response.starts_with("HTTP/1.1 413 Payload Too Large"), "{}", response);
+    }
+
+    #[test]
+    fn malformed_lengths_are_bad_requests() {
+        let response: String = exchange(b"POST /analyze HTTP/1.1\r\nContent-Length: lots\r\n\r\n{}".to_vec());
+
+        assert!(response.starts_with("HTTP/1.1 400 Bad Request"), "{}", response);
+        assert!(response.contains("Content-Length must be a number"), "{}", response);
+    }
+}
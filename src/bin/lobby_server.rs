@@ -0,0 +1,17 @@
+use connect_4::lobby;
+
+// only this machine by default; pass `0.0.0.0:4444` to take players from the network
+const DEFAULT_ADDR: &str = "127.0.0.1:4444";
+
+/**
+ Standalone server hosting many concurrent games over a line-delimited JSON protocol <br/>
+ Usage: `lobby_server [address]` (defaults to `127.0.0.1:4444`)
+ */
+fn main() {
+    let addr: String = std::env::args().nth(1).unwrap_or_else(|| String::from(DEFAULT_ADDR));
+
+    if let Err(err) = lobby::serve(&addr) {
+        eprintln!("failed to start lobby on {}: {}", addr, err);
+        std::process::exit(1);
+    }
+}
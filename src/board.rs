@@ -1,4 +1,9 @@
-use colored::{Colorize, CustomColor};
+use std::io::{self, Write};
+
+use colored::{Color, ColoredString, Colorize, CustomColor};
+
+use crate::eval::EvalWeights;
+use crate::theme::Theme;
 
 pub const RED_PIECE: char = 'R';
 pub const YELLOW_PIECE: char = 'Y';
@@ -9,69 +14,142 @@ pub const BOARD_SIZE: usize = BOARD_HEIGHT * BOARD_WIDTH;
 pub const BOARD_HEIGHT: usize = 6;
 pub const BOARD_WIDTH: usize = 7;
 
+/** Optional extras drawn alongside the board by `write_board_with` */
+#[derive(Default)]
+pub struct BoardOverlay {
+    // per-column scores for the side to move, drawn under the column letters
+    pub scores: Option<[Option<i16>; BOARD_WIDTH]>,
+    // (column, row) of a piece to highlight, e.g. the one just placed
+    pub highlight: Option<(usize, usize)>,
+    // (column, piece) of a disc hovering above the board, waiting to be dropped
+    pub cursor: Option<(usize, char)>,
+}
 
 /**
  Function for displaying the board with appropriate colors for all pieces
+ * `out` - where to draw the board (stdout, or a buffer in tests)
  * `board` - the board currently being used in the game
+ * `theme` - glyphs and colours to draw with
  */
-pub fn display_board(board: &[char; BOARD_SIZE]) {
-    let board_color: CustomColor = CustomColor::new(36, 101, 181);
+pub fn write_board(out: &mut dyn Write, board: &[char; BOARD_SIZE], theme: &Theme) -> io::Result<()> { write_board_with(out, board, &BoardOverlay::default(), theme) }
 
-    println!("  A   B   C   D   E   F   G");
-    println!("  ↓   ↓   ↓   ↓   ↓   ↓   ↓");
-    println!("{}", "_____________________________".custom_color(board_color));
+/**
+ Displays the board along with any extras from the overlay
+ * `out` - where to draw the board (stdout, or a buffer in tests)
+ * `board` - the board currently being used in the game
+ * `overlay` - extras to draw (e.g. per-column scores, a highlighted piece)
+ * `theme` - glyphs and colours to draw with
+ */
+pub fn write_board_with(out: &mut dyn Write, board: &[char; BOARD_SIZE], overlay: &BoardOverlay, theme: &Theme) -> io::Result<()> {
+    let board_color: Option<Color> = theme.frame_color;
+
+    if let Some((col, piece)) = overlay.cursor {
+        writeln!(out, "{}{}", " ".repeat(2 + col * 4), piece_text(theme, piece, false))?;
+    }
+    writeln!(out, "  A   B   C   D   E   F   G")?;
+    if let Some(scores) = &overlay.scores { write_scores(out, scores, theme)?; }
+    writeln!(out, "{}", format!("  {} ", theme.arrow_glyph).repeat(BOARD_WIDTH).trim_end())?;
+    writeln!(out, "{}", paint("_____________________________", board_color))?;
     
     for x in 0..BOARD_HEIGHT {
         for y in 0..BOARD_WIDTH {
-            let piece: char = get_piece_at(board, y, x);
+            let piece: ColoredString = piece_text(theme, get_piece_at(board, y, x), overlay.highlight == Some((y, x)));
 
             if y != 6 {
-                print!("{}", "| ".custom_color(board_color));
-                
-                if piece == EMPTY {
-                    print!("{} ", piece.to_string());
-                }
-                else if piece == RED_PIECE {
-                    print!("{} ", PIECE_ICON.to_string().red());
-                }
-                else {
-                    print!("{} ", PIECE_ICON.to_string().yellow());
-                }
+                write!(out, "{}", paint("| ", board_color))?;
+                write!(out, "{} ", piece)?;
             }
             else {
-                print!("{}", "| ".custom_color(board_color));
-                
-                if piece == EMPTY {
-                    print!("{}", piece.to_string());
-                }
-                else if piece == RED_PIECE {
-                    print!("{}", PIECE_ICON.to_string().red());
-                }
-                else {
-                    print!("{}", PIECE_ICON.to_string().yellow());
-                }
-
-                print!("{}", " |".custom_color(board_color));
+                write!(out, "{}", paint("| ", board_color))?;
+                write!(out, "{}", piece)?;
+                write!(out, "{}", paint(" |", board_color))?;
             }
         }
         
         if x != BOARD_HEIGHT - 1 {
-            println!("{}", "\n|---|---|---|---|---|---|---|".custom_color(board_color));
+            writeln!(out, "{}", paint("\n|---|---|---|---|---|---|---|", board_color))?;
         }
     }
 
-    println!("{}", "\n‾‾‾‾‾‾‾‾‾‾‾‾‾‾‾‾‾‾‾‾‾‾‾‾‾‾‾‾‾".custom_color(board_color));
+    writeln!(out, "{}", paint(&format!("\n{}", theme.base_glyph.to_string().repeat(29)), board_color))?;
+    return out.flush();
+}
+
+/**
+ The text for a single square of the board
+ * `theme` - glyphs and colours to draw with
+ * `piece` - the piece in the square
+ * `highlighted` - should the piece stand out (drawn reversed)?
+ */
+fn piece_text(theme: &Theme, piece: char, highlighted: bool) -> ColoredString {
+    let text: ColoredString = paint(&theme.glyph(piece).to_string(), theme.color(piece));
+    let text: ColoredString = if theme.bold_discs && piece != EMPTY { text.bold() } else { text };
+
+    return if highlighted { text.bold().reversed() } else { text };
+}
+
+/**
+ Colors text if the theme gives it a color
+ * `text` - the text to color
+ * `color` - the color, or `None` for the terminal's own
+ */
+fn paint(text: &str, color: Option<Color>) -> ColoredString {
+    return match color {
+        Some(color) => text.color(color),
+        None => text.normal(),
+    };
+}
+
+/**
+ Prints one score per column, lined up under the column letters and colored from losing (red) to winning (green) <br/>
+ Colorless themes leave the scores uncolored
+ * `out` - where the board is being drawn
+ * `scores` - per-column scores, `None` for filled columns
+ * `theme` - the theme the board is drawn in
+ */
+fn write_scores(out: &mut dyn Write, scores: &[Option<i16>; BOARD_WIDTH], theme: &Theme) -> io::Result<()> {
+    let legal: Vec<i16> = scores.iter().flatten().copied().collect();
+    let low: i16 = legal.iter().copied().min().unwrap_or(0);
+    let high: i16 = legal.iter().copied().max().unwrap_or(0);
+
+    write!(out, " ")?;
+    for score in scores {
+        let score: i16 = match score {
+            Some(score) => *score,
+            None => { write!(out, "    ")?; continue; }
+        };
+
+        let text: String = match score {
+            i16::MAX => String::from("W"),
+            i16::MIN => String::from("L"),
+            _ => score.clamp(-99, 99).to_string(),
+        };
+
+        // 0.0 = worst legal column, 1.0 = best; forced results are always fully red/green
+        let rank: f32 = match score {
+            i16::MAX => 1.0,
+            i16::MIN => 0.0,
+            _ if high == low => 0.5,
+            _ => (score as f32 - low as f32) / (high as f32 - low as f32),
+        };
+        let color: CustomColor = CustomColor::new((220.0 * (1.0 - rank)) as u8 + 20, (200.0 * rank) as u8 + 20, 40);
+        let text: String = format!("{:^3}", text);
+
+        write!(out, "{} ", if theme.is_colorless() { text.normal() } else { text.custom_color(color) })?;
+    }
+    return writeln!(out);
 }
 
 /**
  Evaluates a sub-section of size 4, returning the score for that sub-section
  * `section` - sub-section (should always be 4)
  * `piece` - the piece to evaluate for
+ * `weights` - what each pattern is worth
  */
-fn evaluate_section(section: &[char], piece: char) -> i16 {
-    if section.iter().count() != 4 { panic!("Section must be 4!"); }
+fn evaluate_section(section: &[char], piece: char, weights: &EvalWeights) -> i32 {
+    if section.len() != 4 { panic!("Section must be 4!"); }
 
-    let mut score: i16 = 0;
+    let mut score: i32 = 0;
     let opp_piece: char = if piece == RED_PIECE { YELLOW_PIECE } else { RED_PIECE };
 
     let piece_count: usize = section.iter().filter(|&x| x == &piece).count(); // count_of(section, piece);
@@ -80,40 +158,43 @@ fn evaluate_section(section: &[char], piece: char) -> i16 {
 
     // 4 in a row (max priority due to possible win)
     if piece_count == 4 {
-        score += 100;
+        score += weights.four as i32;
     }
     // 3 in a row
     else if piece_count == 3 && empty_count == 1 {
-        score += 5;
+        score += weights.three as i32;
     }
     // 2 in a row
     else if piece_count == 2 && empty_count == 2 {
-        score += 2;
+        score += weights.two as i32;
     }
 
     // enemy has a chance to get 4 in a row
     if opp_count == 3 && empty_count == 1 {
-        score -= 4;
+        score += weights.opponent_three as i32;
     }
 
     return score;
 }
 
 /**
- Evaluates the entire board by dividing rows, columns, and diagonals into smaller sub-sections that are each individually evaluated. Then, summing those evaluations up into an encompassing score
+ Evaluates the entire board by dividing rows, columns, and diagonals into smaller sub-sections that are each individually evaluated. Then, summing those evaluations up into an encompassing score <br/>
+ The score never reaches `i16::MIN` or `i16::MAX`, which `minimax` keeps for forced results
  * `board` - the board currently in the game
  * `piece` - the piece to evaluate for
+ * `weights` - what each pattern is worth
  */
-pub fn evaluate_board(board: &[char; BOARD_SIZE], piece: char) -> i16 {
+pub fn evaluate_board(board: &[char; BOARD_SIZE], piece: char, weights: &EvalWeights) -> i16 {
     let window_length: usize = 4;
-    let mut score: i16 = 0;
+    // summed wider than the result, since large weights can add up past an i16
+    let mut score: i32 = 0;
 
     // Scoring the center column (makes the AI prefer putting pieces in this column)
     let mut center_column: [char; BOARD_HEIGHT] = [EMPTY; BOARD_HEIGHT];
-    for r in 0..BOARD_HEIGHT { center_column[r] = get_piece_at(board, 3, r); }
-    let center_count: i16 = center_column.iter().filter(|&x| x == &piece).count() as i16;
+    for (r, square) in center_column.iter_mut().enumerate() { *square = get_piece_at(board, 3, r); }
+    let center_count: i32 = center_column.iter().filter(|&x| x == &piece).count() as i32;
 
-    score += center_count * 4;
+    score += center_count * weights.center as i32;
 
     // Sectioning and evaluating each row
     for r in 0..BOARD_HEIGHT {
@@ -122,7 +203,7 @@ pub fn evaluate_board(board: &[char; BOARD_SIZE], piece: char) -> i16 {
         
         for c in 0..(BOARD_WIDTH - 3) {
             let section: &[char] = &this_row[c..(c + window_length)];
-            score += evaluate_section(section, piece);
+            score += evaluate_section(section, piece, weights);
         }
     }
 
@@ -130,11 +211,11 @@ pub fn evaluate_board(board: &[char; BOARD_SIZE], piece: char) -> i16 {
     for c in 0..BOARD_WIDTH {
         // Create array to only this column
         let mut this_col: [char; BOARD_HEIGHT] = [EMPTY; BOARD_HEIGHT];
-        for r in 0..BOARD_HEIGHT { this_col[r] = get_piece_at(board, c, r); }
+        for (r, square) in this_col.iter_mut().enumerate() { *square = get_piece_at(board, c, r); }
 
         for r in 0..(BOARD_HEIGHT - 3) {
             let section: &[char] = &this_col[r..(r + window_length)];
-            score += evaluate_section(section, piece);
+            score += evaluate_section(section, piece, weights);
         }
     }
 
@@ -162,11 +243,11 @@ pub fn evaluate_board(board: &[char; BOARD_SIZE], piece: char) -> i16 {
         asc_diag_section.push(get_piece_at(board, x, y));
 
 
-        let section_size: usize = asc_diag_section.iter().count();
+        let section_size: usize = asc_diag_section.len();
         for i in 0..section_size {
             if i + window_length < section_size {
                 let sub_section: &[char] = &asc_diag_section[i..(i + window_length)];
-                score += evaluate_section(sub_section, piece)
+                score += evaluate_section(sub_section, piece, weights)
             }
         }
     }
@@ -195,16 +276,16 @@ pub fn evaluate_board(board: &[char; BOARD_SIZE], piece: char) -> i16 {
         dsc_diag_section.push(get_piece_at(board, x, y));
 
 
-        let section_size: usize = dsc_diag_section.iter().count();
+        let section_size: usize = dsc_diag_section.len();
         for i in 0..section_size {
             if i + window_length < section_size {
-                let sub_section: &[char] = &&dsc_diag_section[i..(i + window_length)];
-                score += evaluate_section(sub_section, piece)
+                let sub_section: &[char] = &dsc_diag_section[i..(i + window_length)];
+                score += evaluate_section(sub_section, piece, weights)
             }
         }
     }
 
-    return score;
+    return score.clamp(i16::MIN as i32 + 1, i16::MAX as i32 - 1) as i16;
 }
 
 /**
@@ -378,4 +459,56 @@ pub fn get_piece_at(board: &[char; BOARD_SIZE], x: usize, y: usize) -> char { bo
  * `y` - row (usually)
  * `piece` - the piece to set
  */
-pub fn set_square_at(board: &mut [char; BOARD_SIZE], x: usize, y: usize, piece: char) { board[x + y * BOARD_WIDTH] = piece }
\ No newline at end of file
+pub fn set_square_at(board: &mut [char; BOARD_SIZE], x: usize, y: usize, piece: char) { board[x + y * BOARD_WIDTH] = piece }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use colored::Styles;
+    use crate::theme::{CLASSIC, MONOCHROME};
+
+    /**
+     The board as drawn in plain text
+     * `board` - the board to draw
+     * `overlay` - extras to draw with it
+     */
+    fn drawn(board: &[char; BOARD_SIZE], overlay: &BoardOverlay) -> Vec<String> {
+        // `colored` has one switch for the whole process, which every test turns off
+        colored::control::set_override(false);
+
+        let mut out: Vec<u8> = Vec::new();
+        write_board_with(&mut out, board, overlay, &MONOCHROME).unwrap();
+
+        return String::from_utf8(out).unwrap().lines().map(String::from).collect();
+    }
+
+    #[test]
+    fn scores_line_up_under_their_columns() {
+        let scores: [Option<i16>; BOARD_WIDTH] = [Some(12), None, Some(i16::MAX), Some(i16::MIN), Some(-150), Some(0), Some(3)];
+        let lines: Vec<String> = drawn(&[EMPTY; BOARD_SIZE], &BoardOverlay { scores: Some(scores), ..Default::default() });
+
+        assert_eq!(lines[0], "  A   B   C   D   E   F   G");
+        // full columns are left blank and big scores are held to two digits
+        assert_eq!(lines[1], " 12       W   L  -99  0   3  ");
+        assert_eq!(lines[2], "  ↓   ↓   ↓   ↓   ↓   ↓   ↓");
+    }
+
+    #[test]
+    fn the_cursor_hovers_over_its_column() {
+        let mut board: [char; BOARD_SIZE] = [EMPTY; BOARD_SIZE];
+        drop_at_column(&mut board, 3, RED_PIECE);
+
+        let lines: Vec<String> = drawn(&board, &BoardOverlay { cursor: Some((4, YELLOW_PIECE)), ..Default::default() });
+
+        assert_eq!(lines[0], format!("{}O", " ".repeat(18)));
+        assert_eq!(lines[1], "  A   B   C   D   E   F   G");
+        assert_eq!(lines[14], "| _ | _ | _ | X | _ | _ | _ |");
+        assert_eq!(drawn(&board, &BoardOverlay::default())[0], lines[1]);
+    }
+
+    #[test]
+    fn highlighted_pieces_are_drawn_reversed() {
+        assert!(piece_text(&CLASSIC, RED_PIECE, true).style().contains(Styles::Reversed));
+        assert!(!piece_text(&CLASSIC, RED_PIECE, false).style().contains(Styles::Reversed));
+        assert!(piece_text(&MONOCHROME, EMPTY, false).is_plain());
+    }
+}
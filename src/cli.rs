@@ -0,0 +1,236 @@
+use std::path::PathBuf;
+
+use connect_4::ai_opponent::TieBreak;
+use connect_4::eval::{EvalWeights, EvaluatorKind};
+use connect_4::mcts::Budget;
+use connect_4::presentation;
+use connect_4::theme::{self, Theme};
+use connect_4::tune::TuneSettings;
+
+pub use connect_4::console::Options;
+
+pub const DEFAULT_API_ADDR: &str = "127.0.0.1:8080";
+
+pub const USAGE: &str = "usage:
+  connect_4 [options]                 play in the console
+  connect_4 api [address]             serve the analysis API (default 127.0.0.1:8080)
+  connect_4 replay <file> [options]   step through a saved game
+  connect_4 tune [tune options]       tune the AI's evaluation weights by self-play and write them to a file
+
+options:
+  --hint-depth <1-9>                  search depth used by the `hint` command and the analysis overlay
+  --analysis                          start with per-column scores shown under the board
+  --arrow-keys                        choose columns with the arrow keys instead of typing a letter
+  --tui                               play in the full-screen interface
+  --animate                           show discs falling into place (any key skips)
+  --speed <0.1-10>                    speed up (or slow down) text effects, pauses and animations
+  --instant                           skip text effects, pauses and animations entirely
+  --theme <name>                      draw the board in a theme: classic, high-contrast, color-blind, monochrome or ascii
+  --seed <number>                     start the AI's random choices from this seed, so games can be repeated
+  --deterministic                     make the AI play the same way every game, without a random seed
+  --log <file>                        append every game's events to a file, one JSON object per line
+  --weights <file>                    judge positions with the weights in this file (e.g. from `connect_4 tune`) instead of the config's style
+  --evaluator <name>                  how the AI judges positions: patterns (open twos and threes) or threats (odd/even threat theory)
+  --config <file>                     read settings from this file instead of the default config file
+
+tune options (self-play is slow without optimisations, so run a release build: `cargo run --release -- tune`):
+  --iterations <number>               rounds of self-play, eight games each (default 300)
+  --depth <1-9>                       how far the engines search in self-play (default 3)
+  --seed <number>                     repeat an earlier run's random openings
+  --weights <file>                    start from these weights instead of the standard ones
+  --out <file>                        where to write the tuned weights (default weights.toml)
+  --evaluator <name>                  tune the weights for this evaluator: patterns or threats (default patterns)
+  --baseline <budget>                 also play the weights against Monte Carlo search with this budget (e.g. 2000 playouts, or 500ms)
+  --rival <name>                      also play the tuned weights against the same weights judged by this evaluator (e.g. threats against patterns)
+
+settings saved from the in-game menu go to the config file, and the flags above win over it for one launch";
+
+/** What the binary was asked to do */
+pub enum Command {
+    Play(Options),
+    Api(String),
+    Replay(PathBuf, Options),
+    Tune(TuneSettings),
+}
+
+/**
+ Parses the command line arguments (without the program name)
+ * `args` - the arguments to parse
+ */
+pub fn parse(args: &[String]) -> Result<Command, String> {
+    if args.first().map(String::as_str) == Some("api") {
+        if args.len() > 2 { return Err(format!("unexpected argument '{}'", args[2])); }
+
+        return Ok(Command::Api(args.get(1).cloned().unwrap_or_else(|| String::from(DEFAULT_API_ADDR))));
+    }
+
+    if args.first().map(String::as_str) == Some("replay") {
+        let path: &String = args.get(1).filter(|arg| !arg.starts_with("--")).ok_or_else(|| String::from("replay needs a file"))?;
+
+        return Ok(Command::Replay(PathBuf::from(path), parse_options(&args[2..])?));
+    }
+
+    if args.first().map(String::as_str) == Some("tune") { return Ok(Command::Tune(parse_tune_settings(&args[1..])?)); }
+
+    return Ok(Command::Play(parse_options(args)?));
+}
+
+/**
+ Parses the option flags shared by the game and the replay viewer
+ * `args` - the flags to parse
+ */
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options: Options = Options::default();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--hint-depth" => options.hint_depth = parse_depth(arg, args.next())?,
+            "--analysis" => options.show_analysis = true,
+            "--arrow-keys" => options.arrow_keys = true,
+            "--tui" => options.tui = true,
+            "--animate" => options.animate_drops = true,
+            "--speed" => options.speed = Some(parse_speed(arg, args.next())?),
+            "--instant" => options.instant = true,
+            "--theme" => options.theme = Some(parse_theme(arg, args.next())?),
+            "--seed" => options.tie_break = Some(parse_seed(arg, args.next())?),
+            "--deterministic" => options.tie_break = Some(TieBreak::Deterministic),
+            "--weights" => options.weights = Some(parse_weights(arg, args.next())?),
+            "--evaluator" => options.evaluator = Some(parse_evaluator(arg, args.next())?),
+            "--log" => options.log_path = Some(PathBuf::from(args.next().ok_or_else(|| format!("{} needs a value", arg))?)),
+            "--config" => options.config_path = Some(PathBuf::from(args.next().ok_or_else(|| format!("{} needs a value", arg))?)),
+            _ => return Err(format!("unknown argument '{}'", arg)),
+        }
+    }
+
+    return Ok(options);
+}
+
+/**
+ Parses the flags for `connect_4 tune`
+ * `args` - the flags to parse
+ */
+fn parse_tune_settings(args: &[String]) -> Result<TuneSettings, String> {
+    let mut settings: TuneSettings = TuneSettings::default();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--iterations" => settings.iterations = parse_count(arg, args.next())?,
+            "--depth" => settings.depth = parse_depth(arg, args.next())?,
+            "--seed" => settings.seed = parse_seed_number(arg, args.next())?,
+            "--weights" => settings.start = parse_weights(arg, args.next())?,
+            "--evaluator" => settings.evaluator = parse_evaluator(arg, args.next())?,
+            "--baseline" => settings.baseline = Some(parse_budget(arg, args.next())?),
+            "--rival" => settings.rival = Some(parse_evaluator(arg, args.next())?),
+            "--out" => settings.out = PathBuf::from(args.next().ok_or_else(|| format!("{} needs a value", arg))?),
+            _ => return Err(format!("unknown argument '{}'", arg)),
+        }
+    }
+
+    return Ok(settings);
+}
+
+/**
+ Parses the value after a flag as a search depth between 1 and 9
+ * `flag` - the flag the value belongs to (for error messages)
+ * `value` - the value following the flag, if any
+ */
+fn parse_depth(flag: &str, value: Option<&String>) -> Result<u16, String> {
+    let value: &String = value.ok_or_else(|| format!("{} needs a value", flag))?;
+
+    return match value.parse::<u16>() {
+        Ok(depth) if (1..=9).contains(&depth) => Ok(depth),
+        _ => Err(format!("{} must be a number from 1 to 9, got '{}'", flag, value)),
+    };
+}
+
+/**
+ Parses the value after a flag as a speed multiplier within the supported range
+ * `flag` - the flag the value belongs to (for error messages)
+ * `value` - the value following the flag, if any
+ */
+fn parse_speed(flag: &str, value: Option<&String>) -> Result<f32, String> {
+    let value: &String = value.ok_or_else(|| format!("{} needs a value", flag))?;
+
+    return match value.parse::<f32>() {
+        Ok(speed) if (presentation::MIN_SPEED..=presentation::MAX_SPEED).contains(&speed) => Ok(speed),
+        _ => Err(format!("{} must be a number from {} to {}, got '{}'", flag, presentation::MIN_SPEED, presentation::MAX_SPEED, value)),
+    };
+}
+
+/**
+ Parses the value after a flag as the name of a theme
+ * `flag` - the flag the value belongs to (for error messages)
+ * `value` - the value following the flag, if any
+ */
+fn parse_theme(flag: &str, value: Option<&String>) -> Result<Theme, String> {
+    let value: &String = value.ok_or_else(|| format!("{} needs a value", flag))?;
+
+    return theme::find(value).ok_or_else(|| format!("{} must be one of {}, got '{}'", flag, theme::names(), value));
+}
+
+/**
+ Parses the value after a flag as a seed for the AI's random choices
+ * `flag` - the flag the value belongs to (for error messages)
+ * `value` - the value following the flag, if any
+ */
+fn parse_seed(flag: &str, value: Option<&String>) -> Result<TieBreak, String> { parse_seed_number(flag, value).map(TieBreak::Seeded) }
+
+/**
+ Parses the value after a flag as a seed number
+ * `flag` - the flag the value belongs to (for error messages)
+ * `value` - the value following the flag, if any
+ */
+fn parse_seed_number(flag: &str, value: Option<&String>) -> Result<u64, String> {
+    let value: &String = value.ok_or_else(|| format!("{} needs a value", flag))?;
+
+    return value.parse::<u64>().map_err(|_| format!("{} must be a whole number, got '{}'", flag, value));
+}
+
+/**
+ Parses the value after a flag as a positive whole number
+ * `flag` - the flag the value belongs to (for error messages)
+ * `value` - the value following the flag, if any
+ */
+fn parse_count(flag: &str, value: Option<&String>) -> Result<u32, String> {
+    let value: &String = value.ok_or_else(|| format!("{} needs a value", flag))?;
+
+    return match value.parse::<u32>() {
+        Ok(count) if count > 0 => Ok(count),
+        _ => Err(format!("{} must be a whole number above 0, got '{}'", flag, value)),
+    };
+}
+
+/**
+ Reads the weights file named after a flag
+ * `flag` - the flag the value belongs to (for error messages)
+ * `value` - the value following the flag, if any
+ */
+fn parse_weights(flag: &str, value: Option<&String>) -> Result<EvalWeights, String> {
+    let value: &String = value.ok_or_else(|| format!("{} needs a value", flag))?;
+
+    return EvalWeights::load(&PathBuf::from(value));
+}
+
+/**
+ Parses the value after a flag as the name of an evaluator
+ * `flag` - the flag the value belongs to (for error messages)
+ * `value` - the value following the flag, if any
+ */
+fn parse_evaluator(flag: &str, value: Option<&String>) -> Result<EvaluatorKind, String> {
+    let value: &String = value.ok_or_else(|| format!("{} needs a value", flag))?;
+
+    return EvaluatorKind::parse(value).ok_or_else(|| format!("{} must be patterns or threats, got '{}'", flag, value));
+}
+
+/**
+ Parses the value after a flag as a Monte Carlo search budget
+ * `flag` - the flag the value belongs to (for error messages)
+ * `value` - the value following the flag, if any
+ */
+fn parse_budget(flag: &str, value: Option<&String>) -> Result<Budget, String> {
+    let value: &String = value.ok_or_else(|| format!("{} needs a value", flag))?;
+
+    return Budget::parse(value).ok_or_else(|| format!("{} must be a number of playouts or a time like 500ms or 2s, got '{}'", flag, value));
+}
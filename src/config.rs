@@ -0,0 +1,474 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use colored::CustomColor;
+use serde::Deserialize;
+use toml::{Spanned, Value};
+
+use crate::ai_opponent::TieBreak;
+use crate::difficulty::{self, Difficulty};
+use crate::eval::{self, EvalWeights, EvaluatorKind};
+use crate::presentation::{self, Presentation};
+use crate::theme::{self, Theme};
+
+const HEADER: &str = "# connect_4 settings (edit by hand or from the in-game settings menu)";
+const MAX_NAME_LEN: usize = 20;
+
+/** Every setting, as `section.key` in the config file */
+pub const KEYS: [&str; 18] = [
+    "intro",
+    "players.red",
+    "players.yellow",
+    "colors.red",
+    "colors.yellow",
+    "ai.difficulty",
+    "ai.seed",
+    "ai.evaluator",
+    "ai.style",
+    "ai.weights.four",
+    "ai.weights.three",
+    "ai.weights.two",
+    "ai.weights.opponent_three",
+    "ai.weights.center",
+    "display.speed",
+    "display.instant",
+    "display.animate_drops",
+    "display.theme",
+];
+
+/** Whether the title card plays at startup */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntroMode {
+    Ask,
+    Always,
+    Never,
+}
+
+/**
+ Settings kept between launches, stored as TOML in the platform config directory <br/>
+ Anything left out of the file keeps its default
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    pub intro: IntroMode,
+    pub red_name: String,
+    pub yellow_name: String,
+    pub red_color: CustomColor,
+    pub yellow_color: CustomColor,
+    // `None` asks for a difficulty before every game against the AI
+    pub ai_difficulty: Option<Difficulty>,
+    // `None` starts the AI from a new random seed every game
+    pub ai_seed: Option<TieBreak>,
+    pub ai_evaluator: EvaluatorKind,
+    // set from a named style, or edited one by one for a custom style
+    pub ai_weights: EvalWeights,
+    pub presentation: Presentation,
+    pub animate_drops: bool,
+    pub theme: Theme,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            intro: IntroMode::Ask,
+            red_name: String::from("Player 1"),
+            yellow_name: String::from("Player 2"),
+            red_color: CustomColor::new(196, 88, 76),
+            yellow_color: CustomColor::new(208, 208, 23),
+            ai_difficulty: None,
+            ai_seed: None,
+            ai_evaluator: EvaluatorKind::default(),
+            ai_weights: EvalWeights::default(),
+            presentation: Presentation::default(),
+            animate_drops: false,
+            theme: Theme::default(),
+        }
+    }
+}
+
+// the file as written, with where each value sits so problems can point at a line
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct RawConfig {
+    intro: Option<Spanned<Value>>,
+    players: Option<RawPair>,
+    colors: Option<RawPair>,
+    ai: Option<RawAi>,
+    display: Option<RawDisplay>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawPair {
+    red: Option<Spanned<Value>>,
+    yellow: Option<Spanned<Value>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawAi {
+    difficulty: Option<Spanned<Value>>,
+    // written by versions that only had search depths; read as `difficulty`
+    depth: Option<Spanned<Value>>,
+    seed: Option<Spanned<Value>>,
+    evaluator: Option<Spanned<Value>>,
+    style: Option<Spanned<Value>>,
+    weights: Option<RawWeights>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawWeights {
+    four: Option<Spanned<Value>>,
+    three: Option<Spanned<Value>>,
+    two: Option<Spanned<Value>>,
+    opponent_three: Option<Spanned<Value>>,
+    center: Option<Spanned<Value>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawDisplay {
+    speed: Option<Spanned<Value>>,
+    instant: Option<Spanned<Value>>,
+    animate_drops: Option<Spanned<Value>>,
+    theme: Option<Spanned<Value>>,
+}
+
+impl Config {
+    /** Where the config file lives by default, e.g. `~/.config/connect_4/config.toml` on Linux */
+    pub fn default_path() -> Option<PathBuf> { dirs::config_dir().map(|dir| dir.join("connect_4").join("config.toml")) }
+
+    /**
+     Parses a config file, reporting every bad value (and the first unknown key) with its line number
+     * `text` - the file's contents
+     */
+    pub fn parse(text: &str) -> Result<Config, String> {
+        let raw: RawConfig = toml::from_str(text).map_err(|err| match err.span() {
+            Some(span) => format!("line {}: {}", line_of(text, span.start), err.message()),
+            None => err.message().to_string(),
+        })?;
+
+        let players: Option<&RawPair> = raw.players.as_ref();
+        let colors: Option<&RawPair> = raw.colors.as_ref();
+        let ai: Option<&RawAi> = raw.ai.as_ref();
+        let weights: Option<&RawWeights> = ai.and_then(|section| section.weights.as_ref());
+        let display: Option<&RawDisplay> = raw.display.as_ref();
+
+        // the style comes before the weights, so individual weights can adjust it
+        let entries: [(&str, Option<&Spanned<Value>>); 19] = [
+            ("intro", raw.intro.as_ref()),
+            ("players.red", players.and_then(|section| section.red.as_ref())),
+            ("players.yellow", players.and_then(|section| section.yellow.as_ref())),
+            ("colors.red", colors.and_then(|section| section.red.as_ref())),
+            ("colors.yellow", colors.and_then(|section| section.yellow.as_ref())),
+            ("ai.difficulty", ai.and_then(|section| section.depth.as_ref())),
+            ("ai.difficulty", ai.and_then(|section| section.difficulty.as_ref())),
+            ("ai.seed", ai.and_then(|section| section.seed.as_ref())),
+            ("ai.evaluator", ai.and_then(|section| section.evaluator.as_ref())),
+            ("ai.style", ai.and_then(|section| section.style.as_ref())),
+            ("ai.weights.four", weights.and_then(|section| section.four.as_ref())),
+            ("ai.weights.three", weights.and_then(|section| section.three.as_ref())),
+            ("ai.weights.two", weights.and_then(|section| section.two.as_ref())),
+            ("ai.weights.opponent_three", weights.and_then(|section| section.opponent_three.as_ref())),
+            ("ai.weights.center", weights.and_then(|section| section.center.as_ref())),
+            ("display.speed", display.and_then(|section| section.speed.as_ref())),
+            ("display.instant", display.and_then(|section| section.instant.as_ref())),
+            ("display.animate_drops", display.and_then(|section| section.animate_drops.as_ref())),
+            ("display.theme", display.and_then(|section| section.theme.as_ref())),
+        ];
+
+        let mut config: Config = Config::default();
+        let mut problems: Vec<String> = Vec::new();
+
+        for (key, value) in entries {
+            let Some(value) = value else { continue; };
+
+            let result: Result<(), String> = match value_text(value.get_ref()) {
+                Some(text) => config.set(key, &text),
+                None => Err(format!("{} must be a single value", key)),
+            };
+
+            if let Err(err) = result { problems.push(format!("line {}: {}", line_of(text, value.span().start), err)); }
+        }
+
+        if !problems.is_empty() { return Err(problems.join("\n")); }
+
+        return Ok(config);
+    }
+
+    /**
+     Reads a config file, falling back to the defaults if it doesn't exist yet
+     * `path` - the file to read
+     */
+    pub fn load(path: &Path) -> Result<Config, String> {
+        let text: String = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(err) => return Err(format!("couldn't read {}: {}", path.display(), err)),
+        };
+
+        return Config::parse(&text).map_err(|err| err.lines().map(|line| format!("{}: {}", path.display(), line)).collect::<Vec<String>>().join("\n"));
+    }
+
+    /**
+     Writes the config file, creating its directory if needed
+     * `path` - the file to write
+     */
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() { std::fs::create_dir_all(dir)?; }
+
+        return std::fs::write(path, self.to_text());
+    }
+
+    /** The config in its file form */
+    pub fn to_text(&self) -> String {
+        let quoted = |key: &str| Value::String(self.get(key)).to_string();
+        let difficulty: String = match self.ai_difficulty {
+            Some(Difficulty::Depth(depth)) => depth.to_string(),
+            _ => quoted("ai.difficulty"),
+        };
+        // TOML integers stop at i64::MAX, so bigger seeds are written as strings
+        let seed: String = match self.ai_seed {
+            Some(TieBreak::Seeded(seed)) if seed <= i64::MAX as u64 => seed.to_string(),
+            _ => quoted("ai.seed"),
+        };
+
+        return format!(
+"{}
+
+# \"ask\", \"always\" or \"never\"
+intro = {}
+
+[players]
+red = {}
+yellow = {}
+
+[colors]
+# hex colours for each player's name and messages
+red = {}
+yellow = {}
+
+[ai]
+# {}, \"adaptive\" (follows your level), \"mcts\" or \"mcts:<playouts or time, e.g. 2s>\" (Monte Carlo search),
+# a search depth from 1 to 9 (never makes mistakes), or \"ask\" to choose before every game
+difficulty = {}
+# a number to make the AI's choices repeatable, \"deterministic\" to skip randomness entirely, or \"random\" for a new seed every game
+seed = {}
+# \"patterns\" counts open twos and threes; \"threats\" also weighs up threats by row (odd rows suit red, even rows yellow)
+evaluator = {}
+# {}, or \"custom\" to keep the weights below
+style = {}
+
+[ai.weights]
+# what the AI counts in each line of four: four in a row, three or two of its discs with the rest empty, or three of yours
+four = {}
+three = {}
+two = {}
+opponent_three = {}
+# what each of its discs in the centre column is worth
+center = {}
+
+[display]
+# {} - {}, higher is faster
+speed = {:?}
+# skip text effects, pauses and animations entirely
+instant = {}
+# show discs falling into place
+animate_drops = {}
+# {}
+theme = {}
+",
+            HEADER, quoted("intro"), quoted("players.red"), quoted("players.yellow"), quoted("colors.red"), quoted("colors.yellow"),
+            difficulty::PERSONAS.map(|persona| persona.name).join(", "), difficulty, seed, quoted("ai.evaluator"), eval::names(), quoted("ai.style"),
+            self.ai_weights.four, self.ai_weights.three, self.ai_weights.two, self.ai_weights.opponent_three, self.ai_weights.center,
+            presentation::MIN_SPEED, presentation::MAX_SPEED, self.presentation.speed, self.presentation.instant, self.animate_drops,
+            theme::names(), quoted("display.theme"));
+    }
+
+    /**
+     A setting's current value, the way it would be typed in the settings menu
+     * `key` - one of `KEYS`
+     */
+    pub fn get(&self, key: &str) -> String {
+        return match key {
+            "intro" => String::from(match self.intro { IntroMode::Ask => "ask", IntroMode::Always => "always", IntroMode::Never => "never" }),
+            "players.red" => self.red_name.clone(),
+            "players.yellow" => self.yellow_name.clone(),
+            "colors.red" => color_text(self.red_color),
+            "colors.yellow" => color_text(self.yellow_color),
+            "ai.difficulty" => self.ai_difficulty.map_or(String::from("ask"), |difficulty| difficulty.to_string()),
+            "ai.seed" => self.ai_seed.map_or(String::from("random"), |seed| seed.to_string()),
+            "ai.evaluator" => self.ai_evaluator.to_string(),
+            "ai.style" => eval::style_of(&self.ai_weights).map_or(String::from("custom"), |style| style.name.to_string()),
+            "ai.weights.four" => self.ai_weights.four.to_string(),
+            "ai.weights.three" => self.ai_weights.three.to_string(),
+            "ai.weights.two" => self.ai_weights.two.to_string(),
+            "ai.weights.opponent_three" => self.ai_weights.opponent_three.to_string(),
+            "ai.weights.center" => self.ai_weights.center.to_string(),
+            "display.speed" => self.presentation.speed.to_string(),
+            "display.instant" => self.presentation.instant.to_string(),
+            "display.animate_drops" => self.animate_drops.to_string(),
+            "display.theme" => self.theme.name.to_string(),
+            _ => String::new(),
+        };
+    }
+
+    /**
+     Changes a setting from its text form, leaving it untouched if the value isn't allowed
+     * `key` - one of `KEYS`
+     * `value` - the new value, e.g. `5`, `ask`, `#ff8800` or `true`
+     */
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let value: &str = value.trim();
+
+        match key {
+            "intro" => self.intro = match value.to_lowercase().as_str() {
+                "ask" => IntroMode::Ask,
+                "always" => IntroMode::Always,
+                "never" => IntroMode::Never,
+                _ => return Err(format!("intro must be \"ask\", \"always\" or \"never\", got '{}'", value)),
+            },
+            "players.red" => self.red_name = parse_name(key, value)?,
+            "players.yellow" => self.yellow_name = parse_name(key, value)?,
+            "colors.red" => self.red_color = parse_color(key, value)?,
+            "colors.yellow" => self.yellow_color = parse_color(key, value)?,
+            "ai.difficulty" => self.ai_difficulty = match Difficulty::parse(value) {
+                _ if value.eq_ignore_ascii_case("ask") => None,
+                Some(difficulty) => Some(difficulty),
+                None => return Err(format!("ai.difficulty must be one of {}, a number from 1 to 9 or \"ask\", got '{}'", difficulty::names(), value)),
+            },
+            "ai.seed" => self.ai_seed = match TieBreak::parse(value) {
+                _ if value.eq_ignore_ascii_case("random") => None,
+                Some(seed) => Some(seed),
+                None => return Err(format!("ai.seed must be a number, \"deterministic\" or \"random\", got '{}'", value)),
+            },
+            "ai.evaluator" => self.ai_evaluator = EvaluatorKind::parse(value).ok_or_else(|| format!("ai.evaluator must be \"patterns\" or \"threats\", got '{}'", value))?,
+            "ai.style" => self.ai_weights = match eval::find(value) {
+                // custom weights are set one by one
+                _ if value.eq_ignore_ascii_case("custom") => self.ai_weights,
+                Some(style) => style.weights,
+                None => return Err(format!("ai.style must be one of {} or \"custom\", got '{}'", eval::names(), value)),
+            },
+            "ai.weights.four" => self.ai_weights.four = parse_weight(key, value)?,
+            "ai.weights.three" => self.ai_weights.three = parse_weight(key, value)?,
+            "ai.weights.two" => self.ai_weights.two = parse_weight(key, value)?,
+            "ai.weights.opponent_three" => self.ai_weights.opponent_three = parse_weight(key, value)?,
+            "ai.weights.center" => self.ai_weights.center = parse_weight(key, value)?,
+            "display.speed" => self.presentation.speed = match value.parse::<f32>() {
+                Ok(speed) if (presentation::MIN_SPEED..=presentation::MAX_SPEED).contains(&speed) => speed,
+                _ => return Err(format!("display.speed must be a number from {} to {}, got '{}'", presentation::MIN_SPEED, presentation::MAX_SPEED, value)),
+            },
+            "display.instant" => self.presentation.instant = parse_bool(key, value)?,
+            "display.animate_drops" => self.animate_drops = parse_bool(key, value)?,
+            "display.theme" => self.theme = theme::find(value).ok_or_else(|| format!("display.theme must be one of {}, got '{}'", theme::names(), value))?,
+            _ => return Err(format!("unknown setting '{}'", key)),
+        }
+
+        return Ok(());
+    }
+}
+
+/**
+ The line (counting from 1) that a byte offset falls on
+ * `text` - the whole file
+ * `offset` - byte offset into `text`
+ */
+fn line_of(text: &str, offset: usize) -> usize { text[..offset.min(text.len())].matches('\n').count() + 1 }
+
+/**
+ Turns a plain TOML value into the text `Config::set` expects (tables and arrays have none)
+ * `value` - the value from the file
+ */
+fn value_text(value: &Value) -> Option<String> {
+    return match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Integer(num) => Some(num.to_string()),
+        Value::Float(num) => Some(num.to_string()),
+        Value::Boolean(flag) => Some(flag.to_string()),
+        _ => None,
+    };
+}
+
+fn parse_name(key: &str, value: &str) -> Result<String, String> {
+    if value.is_empty() || value.chars().count() > MAX_NAME_LEN {
+        return Err(format!("{} must be 1 to {} characters long", key, MAX_NAME_LEN));
+    }
+
+    return Ok(value.to_string());
+}
+
+fn parse_color(key: &str, value: &str) -> Result<CustomColor, String> {
+    let hex: &str = value.strip_prefix('#').unwrap_or(value);
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2).unwrap_or("?"), 16);
+
+    return match (hex.len(), channel(0), channel(2), channel(4)) {
+        (6, Ok(r), Ok(g), Ok(b)) => Ok(CustomColor::new(r, g, b)),
+        _ => Err(format!("{} must be a hex colour like \"#c4584c\", got '{}'", key, value)),
+    };
+}
+
+fn parse_bool(key: &str, value: &str) -> Result<bool, String> {
+    return match value.to_lowercase().as_str() {
+        "true" | "on" | "yes" => Ok(true),
+        "false" | "off" | "no" => Ok(false),
+        _ => Err(format!("{} must be true or false, got '{}'", key, value)),
+    };
+}
+
+fn parse_weight(key: &str, value: &str) -> Result<i16, String> {
+    return match value.parse::<i16>() {
+        Ok(weight) if (-eval::MAX_WEIGHT..=eval::MAX_WEIGHT).contains(&weight) => Ok(weight),
+        _ => Err(format!("{} must be a whole number from {} to {}, got '{}'", key, -eval::MAX_WEIGHT, eval::MAX_WEIGHT, value)),
+    };
+}
+
+fn color_text(color: CustomColor) -> String { format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b) }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bad_values_are_reported_with_their_lines() {
+        let text: &str = "intro = \"never\"\n\n[ai]\ndifficulty = \"grandmaster\"\nseed = 7\n\n[display]\nspeed = 50\n";
+        let problems: Vec<String> = Config::parse(text).unwrap_err().lines().map(String::from).collect();
+
+        assert_eq!(problems.len(), 2);
+        assert!(problems[0].starts_with("line 4: ai.difficulty must be"), "{}", problems[0]);
+        assert!(problems[1].starts_with("line 8: display.speed must be"), "{}", problems[1]);
+    }
+
+    #[test]
+    fn unknown_keys_are_reported_with_their_line() {
+        let problem: String = Config::parse("[players]\nred = \"Ann\"\nblue = \"Bo\"\n").unwrap_err();
+
+        assert!(problem.starts_with("line 3: "), "{}", problem);
+        assert!(problem.contains("blue"), "{}", problem);
+    }
+
+    #[test]
+    fn settings_survive_saving_and_loading() {
+        let mut config: Config = Config::default();
+        let changes: [(&str, &str); 9] = [
+            ("intro", "never"),
+            ("players.red", "Ann"),
+            ("colors.yellow", "#336699"),
+            ("ai.difficulty", "club"),
+            // too big for a TOML integer, so it's written as a string
+            ("ai.seed", "18446744073709551615"),
+            ("ai.evaluator", "threats"),
+            ("ai.weights.center", "7"),
+            ("display.speed", "2.5"),
+            ("display.theme", "ascii"),
+        ];
+        for (key, value) in changes { config.set(key, value).unwrap(); }
+
+        let path: PathBuf = std::env::temp_dir().join(format!("connect_4_config_test_{}.toml", std::process::id()));
+        config.save(&path).unwrap();
+        let loaded: Result<Config, String> = Config::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap(), config);
+    }
+}
@@ -0,0 +1,979 @@
+use std::cell::{Cell, RefCell, RefMut};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use colored::{ColoredString, Colorize, CustomColor};
+
+use crate::{adaptive, ai_opponent, board, difficulty, input, keys, player, presentation, review};
+use crate::adaptive::SkillStore;
+use crate::ai_opponent::TieBreak;
+use crate::config::{self, Config, IntroMode};
+use crate::difficulty::Difficulty;
+use crate::controller::GameController;
+use crate::error::GameError;
+use crate::eval::{EvalWeights, Evaluator, EvaluatorKind};
+use crate::events::{EventLog, GameEvent, GameObserver};
+use crate::game::{GameState, MoveError};
+use crate::input::{LineInput, StdinInput};
+use crate::player::{Column, Player, PlayerInfo, Turn};
+use crate::presentation::Presentation;
+use crate::record::GameRecord;
+use crate::theme::Theme;
+
+pub const DEFAULT_HINT_DEPTH: u16 = 5;
+
+// how long a falling disc spends in each row (at normal speed)
+pub const DROP_FRAME_SECS: f32 = 0.06;
+
+// TODO:
+// Highlight latest placed piece
+// Highlight 4-in-a-row when game is over
+// AI prolongs the game when it doesn't need to; this is usually when there's multiple ways it can win. Almost as if it's "teasing" the player (i guess this is a bug?)
+// ^Important to note that it DOESN'T lose when it does this normally
+
+/** Console game settings that can be given on the command line */
+pub struct Options {
+    pub hint_depth: u16,
+    pub show_analysis: bool,
+    pub arrow_keys: bool,
+    pub tui: bool,
+    pub animate_drops: bool,
+    pub speed: Option<f32>,
+    pub instant: bool,
+    pub theme: Option<Theme>,
+    // `--seed` or `--deterministic`
+    pub tie_break: Option<TieBreak>,
+    // `--weights`, e.g. a file written by `connect_4 tune`
+    pub weights: Option<EvalWeights>,
+    // `--evaluator`
+    pub evaluator: Option<EvaluatorKind>,
+    pub config_path: Option<PathBuf>,
+    pub log_path: Option<PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options { hint_depth: DEFAULT_HINT_DEPTH, show_analysis: false, arrow_keys: false, tui: false, animate_drops: false, speed: None, instant: false, theme: None, tie_break: None, weights: None, evaluator: None, config_path: None, log_path: None }
+    }
+}
+
+impl Options {
+    /**
+     The presentation settings to use: the config file's, with any command line flags on top
+     * `config` - the loaded config file
+     */
+    pub fn presentation(&self, config: &Config) -> Presentation {
+        return Presentation { speed: self.speed.unwrap_or(config.presentation.speed), instant: self.instant || config.presentation.instant, ..config.presentation };
+    }
+
+    /**
+     Should dropped discs fall into place (from the command line or the config file)?
+     * `config` - the loaded config file
+     */
+    pub fn animate_drops(&self, config: &Config) -> bool { self.animate_drops || config.animate_drops }
+
+    /**
+     The theme to draw the board in: the command line's if given, otherwise the config file's
+     * `config` - the loaded config file
+     */
+    pub fn theme(&self, config: &Config) -> Theme { self.theme.unwrap_or(config.theme) }
+
+    /**
+     How the AI breaks ties in the next game: the command line's seed, then the config file's, then a new random seed
+     * `config` - the loaded config file
+     */
+    pub fn tie_break(&self, config: &Config) -> TieBreak { self.tie_break.or(config.ai_seed).unwrap_or_else(TieBreak::random) }
+
+    /**
+     How the AI judges positions: the command line's weights file if given, otherwise the config file's style
+     * `config` - the loaded config file
+     */
+    pub fn weights(&self, config: &Config) -> EvalWeights { self.weights.unwrap_or(config.ai_weights) }
+
+    /**
+     How the AI scores positions: the evaluator from the command line or the config file, with the weights from `weights`
+     * `config` - the loaded config file
+     */
+    pub fn evaluator(&self, config: &Config) -> Evaluator { Evaluator { kind: self.evaluator.unwrap_or(config.ai_evaluator), weights: self.weights(config) } }
+}
+
+/**
+ The console game: every prompt reads a line from `input` and every view is written to `output` <br/>
+ The real game runs on stdin and stdout; tests can script a whole session with any `BufRead` (through `input::ReaderInput`) and a buffer
+ */
+pub struct Console<I: LineInput, W: Write> {
+    input: RefCell<I>,
+    output: RefCell<W>,
+    // is this the real terminal? Only then is the screen cleared and are keys read directly (arrow keys, skipping animations)
+    terminal: bool,
+    // speed, pauses, colour and screen clears, changeable from the settings menu
+    presentation: Cell<Presentation>,
+    // how the board is drawn (before the presentation settings take its colour away)
+    theme: Cell<Theme>,
+}
+
+impl Console<StdinInput, io::Stdout> {
+    /** The console game on stdin and stdout */
+    pub fn stdio() -> Console<StdinInput, io::Stdout> {
+        let terminal: bool = io::stdout().is_terminal();
+
+        Console { input: RefCell::new(StdinInput), output: RefCell::new(io::stdout()), terminal, presentation: Cell::new(Presentation::default().for_output(terminal, presentation::no_color_requested())), theme: Cell::new(Theme::default()) }
+    }
+}
+
+impl<I: LineInput, W: Write> Console<I, W> {
+    /**
+     Creates a console game on any input and output, e.g. a scripted session for tests <br/>
+     The output isn't a terminal, so it gets plain text with no delays until `apply_display_settings` says otherwise
+     * `input` - where typed lines come from
+     * `output` - where prompts and the board are written
+     */
+    pub fn new(input: I, output: W) -> Console<I, W> {
+        Console { input: RefCell::new(input), output: RefCell::new(output), terminal: false, presentation: Cell::new(Presentation::default().for_output(false, false)), theme: Cell::new(Theme::default()) }
+    }
+
+    /**
+     Switches to the speed and theme from the config, with any command line flags on top, adjusted for where output is going
+     * `options` - command line settings
+     * `config` - settings kept between launches
+     */
+    pub fn apply_display_settings(&self, options: &Options, config: &Config) {
+        self.presentation.set(options.presentation(config).for_output(self.terminal, presentation::no_color_requested()));
+        self.theme.set(options.theme(config));
+    }
+
+    /** Ends the session, handing back everything that was written */
+    pub fn into_output(self) -> W { self.output.into_inner() }
+
+    /**
+     Runs the console game: the title card, then game after game until the input is closed
+     * `options` - game settings from the command line
+     * `config` - settings kept between launches (changeable from the settings menu)
+     * `config_path` - where changed settings are saved
+     */
+    pub fn play(&self, options: &Options, config: &mut Config, config_path: Option<&Path>) -> Result<(), GameError> {
+        // Intro card section
+        match config.intro {
+            IntroMode::Always => self.intro_card()?,
+            IntroMode::Never => {},
+            IntroMode::Ask => {
+                write!(self.out(), "Play title card (y/n): ")?;
+                self.out().flush()?;
+
+                let user_y_n: String = self.read_line()?;
+
+                if user_y_n.to_lowercase().trim() == "y"{
+                    self.intro_card()?;
+                }
+            }
+        }
+
+        // Game starts
+        loop {
+            // container for AI's difficulty setting
+            let mut difficulty_setting: Difficulty = Difficulty::Depth(2);
+
+            // adaptive levels live next to the config file (and only last until the game is closed without one)
+            let skill_path: Option<PathBuf> = config_path.map(SkillStore::path_for);
+            let mut skills: SkillStore = self.load_skills(skill_path.as_deref())?;
+
+            // bool to track if player wants to play vs AI
+            let vs_ai: bool;
+
+            // Prompt user if they want to play vs an AI
+            loop {
+                self.clear_console()?;
+
+                write!(self.out(), "Play vs AI (y/n, or 's' for settings): ")?;
+
+                self.out().flush()?;
+                let user_y_n: String = self.read_line()?;
+
+                if user_y_n.trim().to_lowercase() == "s" {
+                    self.settings_menu(config, config_path, options)?;
+                }
+                else if user_y_n.trim().to_lowercase() == "y" {
+                    vs_ai = true;
+                    break;
+                }
+                else if user_y_n.trim().to_lowercase() == "n" {
+                    vs_ai = false;
+                    break;
+                }
+                else {
+                    self.clear_console()?;
+                    writeln!(self.out(), "{}", "invalid choice".red())?;
+                    self.wait_for_seconds(1.5);
+                }
+            }
+
+            // Prompt user for the AI's difficulty (a persona, or a plain search depth), unless the config already picked one
+            if let (true, Some(difficulty)) = (vs_ai, config.ai_difficulty) {
+                difficulty_setting = difficulty;
+            }
+            else if vs_ai {
+                loop {
+                    self.clear_console()?;
+
+                    writeln!(self.out(), "{}", "Difficulties:".underline())?;
+                    for persona in difficulty::PERSONAS {
+                        self.type_writer(&format!("{:<10}{}", persona.name, persona.description), 0.1, true, CustomColor::new(0, 0, 0))?;
+                    }
+                    self.type_writer(&format!("{:<10}matches your level, getting stronger as you win (now {})", "adaptive", adaptive::describe(skills.level(&config.red_name))), 0.1, true, CustomColor::new(0, 0, 0))?;
+                    self.type_writer(&format!("{:<10}plays out thousands of random games instead of searching (or set its budget: mcts:5000, mcts:2s)", "mcts"), 0.1, true, CustomColor::new(0, 0, 0))?;
+
+                    self.type_writer("\nOr a search depth for the classic engine, which never slips up:", 0.2, true, CustomColor::new(0, 0, 0))?;
+                    self.type_writer("1\n2 - easy\n3\n4 - moderate\n5\n6 - difficult\n7\n8\n9 - virtually unbeatable", 0.2, true, CustomColor::new(0, 0, 0))?;
+
+                    self.type_writer("\nHigher depth selections means higher calculation time; I am NOT responsible for frying a school laptop!", 0.2, true, CustomColor::new(0, 0, 0))?;
+                    self.type_writer("Enter a difficulty or search depth [1 - 9] for the AI: ", 0.2, false, CustomColor::new(0, 0, 0))?;
+
+                    let temp_str: String = self.read_line()?;
+
+                    match Difficulty::parse(&temp_str) {
+                        Some(difficulty) => {
+                            difficulty_setting = difficulty;
+                            break;
+                        },
+                        None => {
+                            self.clear_console()?;
+                            self.type_writer(&format!("Enter one of {}, or a number from 1 to 9", difficulty::names()), 1.5, true, CustomColor::new(196,88,76))?;
+                            self.wait_for_seconds(0.75);
+                        }
+                    }
+                }
+            }
+
+            // saved with the game, so it can be played again exactly
+            let tie_break: TieBreak = options.tie_break(config);
+
+            let red: Box<dyn Player> = Box::new(ConsoleHuman::new(self, config, board::RED_PIECE, options));
+            let yellow: Box<dyn Player> = match vs_ai {
+                false => Box::new(ConsoleHuman::new(self, config, board::YELLOW_PIECE, options)),
+                true => player::ai_player(difficulty_setting, skills.level(&config.red_name), tie_break, options.evaluator(config)),
+            };
+
+            // shown while the AI thinks, so the player can see the level they're playing at
+            let adaptive_game: bool = vs_ai && difficulty_setting == Difficulty::Adaptive;
+            let ai_level: Option<String> = adaptive_game.then(|| format!("adaptive level {}", adaptive::describe(skills.level(&config.red_name))));
+
+            let mut controller: GameController = GameController::new(red, yellow);
+            controller.add_observer(Box::new(ConsoleView { console: self, config, animate_drops: options.animate_drops(config), ai_level }));
+
+            // `--log <file>` appends every game's events to the file, one JSON object per line
+            if let Some(path) = &options.log_path {
+                let log_file: std::fs::File = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+                controller.add_observer(Box::new(EventLog::new(log_file)));
+            }
+
+            controller.run()?;
+
+            if adaptive_game {
+                self.update_skill(&mut skills, skill_path.as_deref(), controller.game(), &config.red_name)?;
+            }
+
+            self.post_game(controller.game().moves(), vs_ai.then_some(tie_break), options.hint_depth, config)?;
+        }
+    }
+
+    /**
+     Steps forward and backward through a saved game, highlighting the piece each move placed
+     * `record` - the game to replay
+     */
+    pub fn replay(&self, record: &GameRecord) -> Result<(), GameError> {
+        // positions[i] is the board after i moves, placed[i] is where move i + 1 landed
+        let mut game: GameState = GameState::new();
+        let mut positions: Vec<[char; board::BOARD_SIZE]> = vec![*game.board()];
+        let mut placed: Vec<(usize, usize)> = Vec::new();
+
+        for &col in &record.moves {
+            placed.push(game.play(col).expect("records are checked when loaded"));
+            positions.push(*game.board());
+        }
+
+        let total: usize = record.moves.len();
+        let mut current: usize = 0;
+
+        loop {
+            self.clear_console()?;
+
+            let overlay: board::BoardOverlay = board::BoardOverlay { highlight: current.checked_sub(1).map(|i| placed[i]), ..Default::default() };
+            board::write_board_with(&mut *self.out(), &positions[current], &overlay, &self.theme())?;
+
+            if current == 0 {
+                match record.seed {
+                    Some(seed) => writeln!(self.out(), "Start of game ({} moves, AI seed {})", total, seed)?,
+                    None => writeln!(self.out(), "Start of game ({} moves)", total)?,
+                }
+            }
+            else {
+                writeln!(self.out(), "Move {} of {}: Player {} played {}", current, total, if current % 2 == 1 { 1 } else { 2 }, col_to_letter(record.moves[current - 1]))?;
+            }
+
+            write!(self.out(), "\n[ENTER/n] next  [p] previous  [f] first  [l] last  [number] jump to move  [q] quit: ")?;
+            self.out().flush()?;
+
+            let command: String = self.read_line()?;
+
+            match command.trim().to_lowercase().as_str() {
+                "" | "n" => current = (current + 1).min(total),
+                "p" => current = current.saturating_sub(1),
+                "f" => current = 0,
+                "l" => current = total,
+                "q" => return Ok(()),
+                other => match other.parse::<usize>() {
+                    Ok(number) if number <= total => current = number,
+                    _ => {
+                        writeln!(self.out(), "{}", format!("Enter a move number from 0 to {}", total).red())?;
+                        self.wait_for_seconds(1.5);
+                    }
+                },
+            }
+        }
+    }
+
+    /**
+     Reads every player's adaptive level, reporting a broken file and starting fresh rather than refusing to play
+     * `path` - where the levels are kept, if anywhere
+     */
+    fn load_skills(&self, path: Option<&Path>) -> Result<SkillStore, GameError> {
+        let Some(path) = path else { return Ok(SkillStore::default()); };
+
+        return match SkillStore::load(path) {
+            Ok(skills) => Ok(skills),
+            Err(err) => {
+                writeln!(self.out(), "{}", format!("Couldn't read adaptive levels, starting fresh: {}", err).red())?;
+                self.wait_for_seconds(2.5);
+                Ok(SkillStore::default())
+            }
+        };
+    }
+
+    /**
+     Moves the player's adaptive level after a game against the AI, then shows and saves the new level
+     * `skills` - every player's level
+     * `path` - where the levels are kept, if anywhere
+     * `game` - the finished game (the player was red)
+     * `player` - the player's name
+     */
+    fn update_skill(&self, skills: &mut SkillStore, path: Option<&Path>, game: &GameState, player: &str) -> Result<(), GameError> {
+        let result: f32 = adaptive::red_result(game);
+        let accuracy: f32 = adaptive::accuracy(game.moves(), board::RED_PIECE);
+        let before: f32 = skills.level(player);
+        let after: f32 = skills.record_game(player, result, accuracy);
+
+        writeln!(self.out(), "\n{}'s adaptive level: {:.1} -> {} ({:.0}% of moves kept the result)", player, before, adaptive::describe(after), accuracy * 100.0)?;
+
+        if let Some(path) = path {
+            if let Err(err) = skills.save(path) {
+                writeln!(self.out(), "{}", format!("Couldn't save adaptive levels: {}", err).red())?;
+            }
+        }
+
+        return Ok(());
+    }
+
+    /** Where everything is written */
+    fn out(&self) -> RefMut<'_, W> { self.output.borrow_mut() }
+
+    /** The theme the board is drawn in, without its colour if the presentation settings turn colour off */
+    fn theme(&self) -> Theme { self.theme.get().for_presentation(&self.presentation.get()) }
+
+    /** Reads the next line, for prompts where stale keystrokes don't matter */
+    fn read_line(&self) -> Result<String, GameError> { input::read_line(&mut *self.input.borrow_mut()) }
+
+    /** Throws away anything typed early, then reads the answer to the prompt that was just shown */
+    fn read_fresh_line(&self) -> Result<String, GameError> { input::read_fresh_line(&mut *self.input.borrow_mut()) }
+
+    /**
+     Prints the recommended column for a player along with a short reason, then waits for ENTER
+     * `playing_board` - the board being used for the game
+     * `piece` - the piece to recommend a move for
+     * `hint_depth` - how far ahead the hint searches
+     */
+    fn show_hint(&self, playing_board: &[char; board::BOARD_SIZE], piece: char, hint_depth: u16) -> Result<(), GameError> {
+        writeln!(self.out(), "{}", "Thinking...".yellow())?;
+
+        let (col, reason) = ai_opponent::suggest_move(playing_board, hint_depth, piece);
+        let reason_text: String = match reason {
+            ai_opponent::HintReason::WinNow => String::from("it wins the game right now!"),
+            ai_opponent::HintReason::Block => String::from("it blocks your opponent from connecting four"),
+            ai_opponent::HintReason::BestScore(score) => format!("it has the best position score ({}) looking {} moves ahead", score, hint_depth),
+        };
+
+        self.type_writer(&format!("Hint: drop in column {} - {}", col_to_letter(col), reason_text), 0.75, true, CustomColor::new(19, 194, 22))?;
+        return self.enter_to_continue();
+    }
+
+    /**
+     Offers to save the finished game for replays, then to step through it with the engine's verdict on every move
+     * `moves` - the columns played, in order
+     * `seed` - how the AI broke ties, for games against it
+     * `depth` - search depth used to re-evaluate each position
+     * `config` - player names shown in the review
+     */
+    fn post_game(&self, moves: &[usize], seed: Option<TieBreak>, depth: u16, config: &Config) -> Result<(), GameError> {
+        write!(self.out(), "\nSave this game for replays (enter a file name, or leave empty to skip): ")?;
+        self.out().flush()?;
+
+        let file_name: String = self.read_line()?;
+
+        if !file_name.trim().is_empty() {
+            let record: GameRecord = GameRecord { moves: moves.to_vec(), seed };
+
+            match record.save(std::path::Path::new(file_name.trim())) {
+                Ok(()) => writeln!(self.out(), "Game saved to {}", file_name.trim())?,
+                Err(err) => writeln!(self.out(), "{}", format!("Couldn't save game: {}", err).red())?,
+            }
+        }
+
+        write!(self.out(), "\nReview this game (y/n): ")?;
+        self.out().flush()?;
+        let user_y_n: String = self.read_line()?;
+
+        if user_y_n.trim().to_lowercase() != "y" { return Ok(()); }
+
+        self.clear_console()?;
+        writeln!(self.out(), "{}", "Reviewing...".yellow())?;
+        let reviews: Vec<review::MoveReview> = review::review_game(moves, depth);
+
+        for move_review in &reviews {
+            self.clear_console()?;
+
+            // the position the move was chosen from, with every option's score
+            board::write_board_with(&mut *self.out(), &move_review.board_before, &board::BoardOverlay { scores: Some(move_review.scores), ..Default::default() }, &self.theme())?;
+
+            let verdict_color: CustomColor = match move_review.verdict {
+                review::Verdict::Best => CustomColor::new(19, 194, 22),
+                review::Verdict::Inaccuracy => CustomColor::new(208, 208, 23),
+                review::Verdict::MissedWin | review::Verdict::Blunder => CustomColor::new(196, 88, 76),
+            };
+
+            writeln!(self.out(), "Move {} of {}: {} played {}", move_review.number, reviews.len(), player_label(config, move_review.piece), col_to_letter(move_review.column))?;
+            writeln!(self.out(), "{}", review::describe(move_review).custom_color(verdict_color))?;
+
+            write!(self.out(), "\nENTER for the next move, 'q' to stop: ")?;
+            self.out().flush()?;
+
+            let buffer: String = self.read_line()?;
+            if buffer.trim().to_lowercase() == "q" { break; }
+        }
+
+        self.clear_console()?;
+        write!(self.out(), "Save the review to a file (enter a file name, or leave empty to skip): ")?;
+        self.out().flush()?;
+
+        let file_name: String = self.read_line()?;
+
+        if !file_name.trim().is_empty() {
+            match std::fs::write(file_name.trim(), review::format_review(moves, &reviews, depth)) {
+                Ok(()) => writeln!(self.out(), "Review saved to {}", file_name.trim())?,
+                Err(err) => writeln!(self.out(), "{}", format!("Couldn't save review: {}", err).red())?,
+            }
+            self.enter_to_continue()?;
+        }
+
+        return Ok(());
+    }
+
+    /**
+     Lets the player change the settings kept between launches, saving after every change
+     * `config` - the settings to change
+     * `config_path` - where to save them (without a config directory, changes last until the game is closed)
+     * `options` - command line settings, which keep winning over the config for this launch
+     */
+    fn settings_menu(&self, config: &mut Config, config_path: Option<&Path>, options: &Options) -> Result<(), GameError> {
+        loop {
+            self.clear_console()?;
+
+            writeln!(self.out(), "{}", "Settings:".underline())?;
+            for (i, key) in config::KEYS.iter().enumerate() {
+                writeln!(self.out(), "{}. {:<24}{}", i + 1, key, config.get(key))?;
+            }
+
+            match config_path {
+                Some(path) => writeln!(self.out(), "\nChanges are saved to {}", path.display())?,
+                None => writeln!(self.out(), "\nNo config directory was found, so changes only last until the game is closed")?,
+            }
+
+            write!(self.out(), "Enter a setting's number to change it (leave empty to go back): ")?;
+            self.out().flush()?;
+
+            let choice: String = self.read_line()?;
+            if choice.trim().is_empty() { return Ok(()); }
+
+            let key: &str = match choice.trim().parse::<usize>() {
+                Ok(num) if (1..=config::KEYS.len()).contains(&num) => config::KEYS[num - 1],
+                _ => {
+                    writeln!(self.out(), "{}", format!("Enter a number from 1 to {}", config::KEYS.len()).red())?;
+                    self.wait_for_seconds(1.5);
+                    continue;
+                }
+            };
+
+            write!(self.out(), "New value for {} (currently {}): ", key, config.get(key))?;
+            self.out().flush()?;
+
+            let value: String = self.read_line()?;
+
+            if let Err(err) = config.set(key, &value) {
+                writeln!(self.out(), "{}", err.red())?;
+                self.wait_for_seconds(2.5);
+                continue;
+            }
+
+            self.apply_display_settings(options, config);
+
+            if let Some(path) = config_path {
+                if let Err(err) = config.save(path) {
+                    writeln!(self.out(), "{}", format!("Couldn't save settings: {}", err).red())?;
+                    self.enter_to_continue()?;
+                }
+            }
+        }
+    }
+
+    /** Plays the fancy intro card :) */
+    fn intro_card(&self) -> Result<(), GameError> {
+        self.clear_console()?;
+
+        let mut freq: u32 = 300;
+        let title_card: [&str; 6] = [
+            "   _____                                  _            _  _   ",
+            "  / ____|                                | |          | || |  ",
+            " | |      ___   _ __   _ __    ___   ___ | |_  ______ | || |_ ",
+            " | |     / _ \\ | '_ \\ | '_ \\  / _ \\ / __|| __||______||__   _|",
+            " | |____| (_) || | | || | | ||  __/| (__ | |_            | |  ",
+            "  \\_____|\\___/ |_| |_||_| |_| \\___| \\___| \\__|           |_|  "
+        ];
+
+        for card in title_card {
+            self.type_writer(card, 0.35, true, CustomColor::new(0, 0, 0))?;
+
+            // creating a thread to stop the pausing of playing the beep
+            beep_no_pause(freq, 250);
+
+            freq += 100;
+        }
+
+        writeln!(self.out())?;
+        self.type_writer("by benjamin n.", 1.5, true, CustomColor::new(0, 0, 0))?;
+        writeln!(self.out())?;
+
+        self.enter_to_continue()?;
+        self.clear_console()?;
+
+        return Ok(());
+    }
+
+    /**
+     Displays the board after a drop, first showing the disc falling through each empty square of its column <br/>
+     Any key skips the rest of the fall. The animation only plays when enabled, outside of instant mode, and on a real terminal
+     * `playing_board` - the board after the piece was dropped
+     * `placed` - (column, row) where the piece landed
+     * `animate` - is the drop animation enabled?
+     */
+    fn display_drop(&self, playing_board: &[char; board::BOARD_SIZE], placed: (usize, usize), animate: bool) -> io::Result<()> {
+        let frame_secs: f32 = self.presentation.get().scale(DROP_FRAME_SECS);
+
+        if animate && frame_secs > 0.0 && self.terminal {
+            let (col, landed_row) = placed;
+            let piece: char = board::get_piece_at(playing_board, col, landed_row);
+
+            let mut frame_board: [char; board::BOARD_SIZE] = *playing_board;
+            board::set_square_at(&mut frame_board, col, landed_row, board::EMPTY);
+
+            for row in 0..landed_row {
+                board::set_square_at(&mut frame_board, col, row, piece);
+                board::write_board(&mut *self.out(), &frame_board, &self.theme())?;
+                board::set_square_at(&mut frame_board, col, row, board::EMPTY);
+
+                let skipped: bool = keys::wait_for_key_or_timeout(std::time::Duration::from_secs_f32(frame_secs))?;
+                self.clear_console()?;
+
+                if skipped { break; }
+            }
+        }
+
+        return board::write_board(&mut *self.out(), playing_board, &self.theme());
+    }
+
+    /**
+     Characters of a string gradually appear on the screen (all at once in instant mode)
+     * `message` - the string to display
+     * `duration` - how long until all characters are displayed, at normal speed
+     * `new_line` - should a new line be printed after all characters are finished printing?
+     */
+    fn type_writer(&self, message: &str, duration: f32, new_line: bool, text_color: CustomColor) -> io::Result<()> {
+        let is_colored: bool = !(text_color.r == 0 && text_color.g == 0 && text_color.b == 0);
+        let duration: f32 = self.presentation.get().scale(duration);
+        // instant mode prints the whole message in one go
+        let pieces: Vec<String> = if duration > 0.0 { message.chars().map(String::from).collect() } else { vec![message.to_string()] };
+        let wait_time: f32 = duration / pieces.len() as f32;
+
+        for piece in pieces {
+            if !is_colored {
+                write!(self.out(), "{}", piece)?;
+            }
+            else {
+                write!(self.out(), "{}", piece.custom_color(text_color))?;
+            }
+
+            self.out().flush()?;
+
+            if wait_time > 0.0 { std::thread::sleep(std::time::Duration::from_secs_f32(wait_time)); }
+        }
+
+        if new_line {
+            writeln!(self.out())?;
+        }
+
+        return Ok(());
+    }
+
+    /**
+     Prints the winner's message in their colour
+     * `piece` - the winning piece (either red piece or yellow piece)
+     * `config` - player names and colours
+     */
+    fn announce_winner(&self, piece: char, config: &Config) -> io::Result<()> {
+        if piece == board::RED_PIECE {
+            return self.type_writer(&format!("{} wins!", config.red_name), 1.0, true, config.red_color);
+        }
+        else {
+            return self.type_writer(&format!("{} wins!", config.yellow_name), 1.0, true, config.yellow_color);
+        }
+    }
+
+    /** Helper function to clear console (skipped when output isn't going to a terminal) */
+    #[cfg(windows)]
+    fn clear_console(&self) -> io::Result<()> {
+        // a line break keeps views apart when the screen isn't being cleared
+        if !self.terminal || !self.presentation.get().clear_screen {
+            return writeln!(self.out());
+        }
+
+        return winconsole::console::clear().map_err(|err| io::Error::other(err.to_string()));
+    }
+
+    /** Helper function to clear console (ANSI fallback for terminals outside of Windows, skipped when output isn't going to a terminal) */
+    #[cfg(not(windows))]
+    fn clear_console(&self) -> io::Result<()> {
+        // a line break keeps views apart when the screen isn't being cleared
+        if !self.terminal || !self.presentation.get().clear_screen {
+            return writeln!(self.out());
+        }
+
+        write!(self.out(), "\x1B[2J\x1B[1;1H")?;
+        return self.out().flush();
+    }
+
+    /**
+     Helper function that provides a quick method to pause for a specified amount of seconds
+     * `secs` - duration in seconds to pause the thread, at normal speed (skipped in instant mode)
+     */
+    fn wait_for_seconds(&self, secs: f32) {
+        let secs: f32 = self.presentation.get().scale(secs);
+
+        if secs > 0.0 { std::thread::sleep(std::time::Duration::from_secs_f32(secs)); }
+    }
+
+    /** Helper function that pauses the thread until the user presses enter */
+    fn enter_to_continue(&self) -> Result<(), GameError> {
+        writeln!(self.out(), "\nPress ENTER to continue")?;
+
+        self.read_fresh_line()?;
+        return Ok(());
+    }
+}
+
+/** A player at this console, typing (or arrowing) their moves */
+struct ConsoleHuman<'c, I: LineInput, W: Write> {
+    // the console the player types into
+    console: &'c Console<I, W>,
+    name: String,
+    // the player's name, in their colour, shown in the prompt
+    label: ColoredString,
+    hint_depth: u16,
+    show_analysis: bool,
+    arrow_keys: bool,
+}
+
+impl<'c, I: LineInput, W: Write> ConsoleHuman<'c, I, W> {
+    /**
+     Creates a console player, starting from the command line's hint and analysis settings
+     * `console` - the console the player types into
+     * `config` - player names and colours
+     * `piece` - the piece this player drops
+     * `options` - game settings from the command line
+     */
+    fn new(console: &'c Console<I, W>, config: &Config, piece: char, options: &Options) -> ConsoleHuman<'c, I, W> {
+        let name: String = if piece == board::RED_PIECE { config.red_name.clone() } else { config.yellow_name.clone() };
+
+        ConsoleHuman { console, name, label: player_label(config, piece), hint_depth: options.hint_depth, show_analysis: options.show_analysis, arrow_keys: options.arrow_keys }
+    }
+
+    /**
+     Function that retrieves the player's input for a column and validates it, returning the chosen column <br/>
+     Typing `hint` instead of a column shows the recommended move, `analysis` toggles per-column scores and `undo` takes back the player's last move <br/>
+     Anything typed before the prompt finished appearing is thrown away, so it can't become a move
+     * `playing_board` - the board being used for the game
+     * `piece` - the piece this player is dropping (used for hints and analysis)
+     */
+    fn get_player_col_input(&mut self, playing_board: [char; 42], piece: char) -> Result<Turn, GameError> {
+        // keys can only be read one at a time from a real terminal
+        if self.arrow_keys && self.console.terminal { return self.get_player_col_arrow_keys(playing_board, piece); }
+
+        let console: &Console<I, W> = self.console;
+
+        // input loop for choosing where to drop a piece
+        loop {
+            console.clear_console()?;
+
+            let mut overlay: board::BoardOverlay = board::BoardOverlay::default();
+            if self.show_analysis {
+                overlay.scores = Some(ai_opponent::score_columns(&playing_board, self.hint_depth, piece));
+            }
+            board::write_board_with(&mut *console.out(), &playing_board, &overlay, &console.theme())?;
+
+            // (0, 0, 0) custom color is just my default for "no color"
+            write!(console.out(), "{} ", self.label)?;
+            console.type_writer("Enter a column ('hint', 'analysis', 'undo') to drop a piece: ", 0.75, false, CustomColor::new(0, 0, 0))?;
+            // Prevents text afterwards from disappearing randomly
+            console.out().flush()?;
+
+            // Read the player's input
+            let user_response: String = console.read_fresh_line()?;
+
+            if user_response.trim().to_lowercase() == "hint" {
+                console.show_hint(&playing_board, piece, self.hint_depth)?;
+                continue;
+            }
+            else if user_response.trim().to_lowercase() == "analysis" {
+                self.show_analysis = !self.show_analysis;
+                continue;
+            }
+            else if user_response.trim().to_lowercase() == "undo" {
+                return Ok(Turn::Undo);
+            }
+
+            // If player's input is valid, return back to the main function
+            // Otherwise, notify the player of invalid input and try again
+            if user_response_valid(&(user_response.trim().to_uppercase()), &playing_board) { return Ok(Turn::Move(letter_to_col(user_response.trim().to_uppercase().chars().next().unwrap()))); }
+            else {
+                console.clear_console()?;
+
+                console.type_writer("Invalid input!\nColumn not recognized or is already filled", 0.35, true, CustomColor::new(196,88,76))?;
+                console.wait_for_seconds(2.5);
+
+                console.clear_console()?;
+            }
+        }
+    }
+
+    /**
+     Lets the player move a disc above the board with the arrow keys (or A - G) and drop it with ENTER or SPACE <br/>
+     Keys are read in raw mode, so held or buffered keys from earlier can't drop a piece by accident
+     * `playing_board` - the board being used for the game
+     * `piece` - the piece this player is dropping
+     */
+    fn get_player_col_arrow_keys(&mut self, playing_board: [char; 42], piece: char) -> Result<Turn, GameError> {
+        let console: &Console<I, W> = self.console;
+        let open_columns: Vec<usize> = board::get_open_columns(&playing_board);
+
+        // start above the open column closest to the centre; a full board means the game is already over
+        let Some(&start) = open_columns.iter().min_by_key(|&&col| col.abs_diff(board::BOARD_WIDTH / 2)) else {
+            return Err(GameError::IllegalMove(board::BOARD_WIDTH / 2, MoveError::GameOver));
+        };
+        let mut cursor: usize = start;
+
+        loop {
+            console.clear_console()?;
+
+            let mut overlay: board::BoardOverlay = board::BoardOverlay { cursor: Some((cursor, piece)), ..Default::default() };
+            if self.show_analysis {
+                overlay.scores = Some(ai_opponent::score_columns(&playing_board, self.hint_depth, piece));
+            }
+            board::write_board_with(&mut *console.out(), &playing_board, &overlay, &console.theme())?;
+
+            writeln!(console.out(), "{} Move with {} or A-G, drop with ENTER/SPACE (H = hint, S = scores, U = undo, ESC = quit)", self.label, console.theme().arrow_keys)?;
+            console.out().flush()?;
+
+            // full columns are skipped when moving left or right, and can't be jumped to
+            match keys::read_key()? {
+                keys::KeyInput::Left => cursor = open_columns.iter().rev().find(|&&col| col < cursor).copied().unwrap_or(cursor),
+                keys::KeyInput::Right => cursor = open_columns.iter().find(|&&col| col > cursor).copied().unwrap_or(cursor),
+                keys::KeyInput::Column(col) => if open_columns.contains(&col) { cursor = col; },
+                keys::KeyInput::Drop => return Ok(Turn::Move(cursor)),
+                keys::KeyInput::Undo => return Ok(Turn::Undo),
+                keys::KeyInput::Hint => console.show_hint(&playing_board, piece, self.hint_depth)?,
+                keys::KeyInput::ToggleAnalysis => self.show_analysis = !self.show_analysis,
+                keys::KeyInput::Quit => {
+                    console.clear_console()?;
+                    return Err(GameError::Quit);
+                },
+            }
+        }
+    }
+}
+
+impl<I: LineInput, W: Write> Player for ConsoleHuman<'_, I, W> {
+    fn choose_move(&mut self, game: &GameState) -> Result<Column, GameError> {
+        loop {
+            if let Turn::Move(col) = self.choose_turn(game)? { return Ok(col); }
+        }
+    }
+
+    fn choose_turn(&mut self, game: &GameState) -> Result<Turn, GameError> { self.get_player_col_input(*game.board(), game.current_piece()) }
+
+    fn info(&self) -> PlayerInfo { PlayerInfo::Human { name: self.name.clone() } }
+}
+
+/** Draws every move as it's played and announces the result */
+struct ConsoleView<'c, I: LineInput, W: Write> {
+    console: &'c Console<I, W>,
+    config: &'c Config,
+    animate_drops: bool,
+    // e.g. "adaptive level 1.8, between casual and club", shown while the AI thinks
+    ai_level: Option<String>,
+}
+
+impl<I: LineInput, W: Write> GameObserver for ConsoleView<'_, I, W> {
+    fn on_event(&mut self, game: &GameState, event: &GameEvent) -> Result<(), GameError> {
+        match *event {
+            GameEvent::AiThinking { .. } => {
+                self.console.clear_console()?;
+
+                // Displays board while AI selects move
+                board::write_board(&mut *self.console.out(), game.board(), &self.console.theme())?;
+                match &self.ai_level {
+                    Some(level) => writeln!(self.console.out(), "{} ({})", "Thinking...".yellow(), level)?,
+                    None => writeln!(self.console.out(), "{}", "Thinking...".yellow())?,
+                }
+            },
+            GameEvent::MovePlayed { column, row, .. } => {
+                self.console.clear_console()?;
+                self.console.display_drop(game.board(), (column, row), self.animate_drops)?;
+            },
+            GameEvent::GameWon { piece, .. } => self.console.announce_winner(piece, self.config)?,
+            GameEvent::GameDrawn { .. } => self.console.type_writer("Tie! No one wins!", 2.0, true, CustomColor::new(19, 194, 22))?,
+            // the next prompt redraws the board
+            GameEvent::GameStarted { .. } | GameEvent::AiMoveChosen { .. } | GameEvent::Undo { .. } => {},
+        }
+
+        return Ok(());
+    }
+}
+
+/**
+ A player's name in brackets and in their colour, e.g. `[Player 1]`
+ * `config` - player names and colours
+ * `piece` - the player's piece
+ */
+fn player_label(config: &Config, piece: char) -> ColoredString {
+    if piece == board::RED_PIECE { format!("[{}]", config.red_name).custom_color(config.red_color) }
+    else { format!("[{}]", config.yellow_name).custom_color(config.yellow_color) }
+}
+
+/**
+ Helper function that prevents the main thread from being paused in order to play a Windows console beep
+ * `freq` - frequency of the beep (hertz)
+ * `dur` - duration of the beep (milliseconds)
+ */
+#[cfg(windows)]
+fn beep_no_pause(freq: u32, dur: u32) { std::thread::spawn(move || winconsole::console::beep(freq, dur)); }
+
+/** Console beeps are Windows-only, so the title card plays silently elsewhere */
+#[cfg(not(windows))]
+fn beep_no_pause(_freq: u32, _dur: u32) {}
+
+/**
+ Helper function that checks if a users response is valid (utilized in the column input function)
+ * `resp` - the input string to check
+ * `playing_board` - the board currently being used in the game
+ */
+fn user_response_valid(resp: &String, playing_board: &[char; 42]) -> bool { (resp == "A" || resp == "B" || resp == "C" || resp == "D" || resp == "E" || resp == "F" || resp == "G") && board::is_column_open(playing_board, letter_to_col(resp.chars().next().unwrap())) }
+
+/**
+ Helper function to convert a character into a usable index for the "playing_board" array
+ * `col` - the character to convert (A - G)
+ */
+fn letter_to_col(col: char) -> usize { col as usize - 65 }
+
+/**
+ Helper function to convert a column index back into its letter (A - G)
+ * `col` - the column index to convert
+ */
+fn col_to_letter(col: usize) -> char { (b'A' + col as u8) as char }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{ScriptedInput, ScriptedLine};
+
+    fn early(line: &str) -> ScriptedLine { ScriptedLine::Early(String::from(line)) }
+    fn prompted(line: &str) -> ScriptedLine { ScriptedLine::Prompted(String::from(line)) }
+
+    /**
+     A console on scripted input, which isn't a terminal so has no typewriter effects or pauses
+     * `script` - the lines typed, each marked with when it was typed
+     */
+    fn scripted_console(script: Vec<ScriptedLine>) -> Console<ScriptedInput, Vec<u8>> {
+        // `colored` has one switch for the whole process, which every test turns off
+        colored::control::set_override(false);
+
+        return Console::new(ScriptedInput::new(script), Vec::new());
+    }
+
+    /**
+     Red, typing at the column prompt of a console
+     * `console` - the console red types into
+     */
+    fn red_at<'c>(console: &'c Console<ScriptedInput, Vec<u8>>) -> ConsoleHuman<'c, ScriptedInput, Vec<u8>> {
+        return ConsoleHuman::new(console, &Config::default(), board::RED_PIECE, &Options::default());
+    }
+
+    #[test]
+    fn keys_typed_before_the_prompt_are_not_moves() {
+        // "A" and a held ENTER were typed while the AI was thinking
+        let console: Console<ScriptedInput, Vec<u8>> = scripted_console(vec![early("A"), early(""), early(""), prompted("D")]);
+
+        assert_eq!(red_at(&console).choose_move(&GameState::new()).unwrap(), 3);
+        assert_eq!(console.input.borrow().remaining(), 0);
+    }
+
+    #[test]
+    fn each_turn_only_reads_its_own_answer() {
+        let console: Console<ScriptedInput, Vec<u8>> = scripted_console(vec![prompted("D"), early("E"), prompted("C"), early(""), early("G"), prompted("B")]);
+        let mut red: ConsoleHuman<ScriptedInput, Vec<u8>> = red_at(&console);
+
+        let moves: Vec<Column> = (0..3).map(|_| red.choose_move(&GameState::new()).unwrap()).collect();
+
+        assert_eq!(moves, [3, 2, 1]);
+    }
+
+    #[test]
+    fn keys_typed_during_the_invalid_input_pause_are_dropped() {
+        let console: Console<ScriptedInput, Vec<u8>> = scripted_console(vec![prompted("Z"), early("A"), prompted("C")]);
+
+        assert_eq!(red_at(&console).choose_move(&GameState::new()).unwrap(), 2);
+
+        let output: String = String::from_utf8(console.into_output()).unwrap();
+        assert_eq!(output.matches("Invalid input!").count(), 1);
+    }
+
+    #[test]
+    fn aiming_at_a_full_board_is_an_error_not_a_panic() {
+        let console: Console<ScriptedInput, Vec<u8>> = scripted_console(Vec::new());
+        let full_board: [char; board::BOARD_SIZE] = [board::RED_PIECE; board::BOARD_SIZE];
+
+        assert!(matches!(red_at(&console).get_player_col_arrow_keys(full_board, board::YELLOW_PIECE), Err(GameError::IllegalMove(_, MoveError::GameOver))));
+    }
+
+        #[test]
+    fn input_closing_with_only_stale_keys_left_is_an_error() {
+        let console: Console<ScriptedInput, Vec<u8>> = scripted_console(vec![early("A")]);
+
+        assert!(matches!(red_at(&console).choose_move(&GameState::new()), Err(GameError::InputClosed)));
+    }
+}
@@ -0,0 +1,273 @@
+use crate::board;
+use crate::error::GameError;
+use crate::events::{GameEvent, GameObserver};
+use crate::game::{GameState, GameStatus};
+use crate::player::{Column, Player, PlayerInfo, Turn};
+
+/**
+ Runs a game between two players: asks whoever's turn it is for a move, plays it, and tells every observer <br/>
+ Red always moves first
+ */
+pub struct GameController<'a> {
+    game: GameState,
+    // red, then yellow
+    players: [Box<dyn Player + 'a>; 2],
+    observers: Vec<Box<dyn GameObserver + 'a>>,
+    started: bool,
+}
+
+impl<'a> GameController<'a> {
+    /**
+     Sets up a new game between two players
+     * `red` - the player who moves first
+     * `yellow` - the player who moves second
+     */
+    pub fn new(red: Box<dyn Player + 'a>, yellow: Box<dyn Player + 'a>) -> GameController<'a> {
+        GameController { game: GameState::new(), players: [red, yellow], observers: Vec::new(), started: false }
+    }
+
+    /**
+     Registers an observer for every event from now on
+     * `observer` - the observer to add
+     */
+    pub fn add_observer(&mut self, observer: Box<dyn GameObserver + 'a>) { self.observers.push(observer); }
+
+    /** The game so far */
+    pub fn game(&self) -> &GameState { &self.game }
+
+    /** Asks the player whose turn it is for a move (or an undo) and carries it out, returning the game's status afterwards */
+    pub fn play_turn(&mut self) -> Result<GameStatus, GameError> {
+        if !self.started {
+            self.started = true;
+            self.emit(GameEvent::GameStarted { red: self.players[0].info(), yellow: self.players[1].info() })?;
+        }
+
+        let piece: char = self.game.current_piece();
+        let seat: usize = seat_of(piece);
+        let player: PlayerInfo = self.players[seat].info();
+
+        if player.is_ai() { self.emit(GameEvent::AiThinking { piece, player: player.clone() })?; }
+
+        let column: Column = match self.players[seat].choose_turn(&self.game)? {
+            Turn::Move(column) => column,
+            Turn::Undo => {
+                self.undo_for(piece)?;
+                return Ok(self.game.status());
+            }
+        };
+
+        if player.is_ai() { self.emit(GameEvent::AiMoveChosen { piece, column, search: self.players[seat].last_search() })?; }
+
+        let (column, row) = self.game.play(column).map_err(|err| GameError::IllegalMove(column, err))?;
+        self.emit(GameEvent::MovePlayed { number: self.game.moves().len(), piece, column, row })?;
+
+        match self.game.status() {
+            GameStatus::Won(winner) => self.emit(GameEvent::GameWon { piece: winner, moves: self.game.moves().to_vec() })?,
+            GameStatus::Draw => self.emit(GameEvent::GameDrawn { moves: self.game.moves().to_vec() })?,
+            GameStatus::InProgress => {},
+        }
+
+        return Ok(self.game.status());
+    }
+
+    /** Plays turns until the game is won or drawn, then lets both players know it's over */
+    pub fn run(&mut self) -> Result<GameStatus, GameError> {
+        while self.game.status() == GameStatus::InProgress {
+            self.play_turn()?;
+        }
+
+        for player in self.players.iter_mut() {
+            player.game_over(&self.game)?;
+        }
+
+        return Ok(self.game.status());
+    }
+
+    /**
+     Takes back moves until `piece`'s most recent move is gone, so it's their turn again (against the AI that's
+     the AI's reply and the player's own move) <br/>
+     Nothing happens if `piece` hasn't moved yet. Returns how many moves were taken back
+     * `piece` - the piece asking for the undo
+     */
+    pub fn undo_for(&mut self, piece: char) -> Result<usize, GameError> {
+        let moves: usize = self.game.moves().len();
+        let own_moves: usize = if piece == board::RED_PIECE { moves.div_ceil(2) } else { moves / 2 };
+        if own_moves == 0 { return Ok(0); }
+
+        // moves alternate red, yellow, ..., so `piece`'s last move is the last or second to last one
+        let last_own: usize = if (moves % 2 == 1) == (piece == board::RED_PIECE) { moves } else { moves - 1 };
+
+        for number in (last_own..=moves).rev() {
+            let undone_piece: char = if number % 2 == 1 { board::RED_PIECE } else { board::YELLOW_PIECE };
+            let Some((column, row)) = self.game.undo() else { break; };
+
+            self.emit(GameEvent::Undo { number, piece: undone_piece, column, row })?;
+        }
+
+        return Ok(moves - self.game.moves().len());
+    }
+
+    fn emit(&mut self, event: GameEvent) -> Result<(), GameError> {
+        for observer in self.observers.iter_mut() {
+            observer.on_event(&self.game, &event)?;
+        }
+
+        return Ok(());
+    }
+}
+
+/**
+ Index into `players` for a piece
+ * `piece` - red or yellow
+ */
+fn seat_of(piece: char) -> usize { if piece == board::RED_PIECE { 0 } else { 1 } }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+
+    use crate::ai_opponent::TieBreak;
+    use crate::eval::{EvalWeights, Evaluator, EvaluatorKind};
+    use crate::player::{MinimaxPlayer, RandomPlayer, RemotePlayer};
+
+    /** Takes a fixed list of turns, standing in for a person */
+    struct ScriptedPlayer {
+        turns: VecDeque<Turn>,
+    }
+
+    impl ScriptedPlayer {
+        fn moves(columns: &[Column]) -> Box<ScriptedPlayer> { Box::new(ScriptedPlayer { turns: columns.iter().map(|&col| Turn::Move(col)).collect() }) }
+    }
+
+    impl Player for ScriptedPlayer {
+        fn choose_move(&mut self, game: &GameState) -> Result<Column, GameError> {
+            loop {
+                if let Turn::Move(col) = self.choose_turn(game)? { return Ok(col); }
+            }
+        }
+
+        fn choose_turn(&mut self, _game: &GameState) -> Result<Turn, GameError> { self.turns.pop_front().ok_or(GameError::InputClosed) }
+
+        fn info(&self) -> PlayerInfo { PlayerInfo::Human { name: String::from("scripted") } }
+    }
+
+    /** Writes down the name of every event it sees */
+    struct EventNames(Rc<RefCell<Vec<String>>>);
+
+    impl GameObserver for EventNames {
+        fn on_event(&mut self, _game: &GameState, event: &GameEvent) -> Result<(), GameError> {
+            let json: serde_json::Value = serde_json::to_value(event).unwrap();
+            self.0.borrow_mut().push(json["event"].as_str().unwrap().to_string());
+
+            return Ok(());
+        }
+    }
+
+    /** Writes down who is playing red and yellow when the game starts */
+    struct Lineup(Rc<RefCell<Vec<PlayerInfo>>>);
+
+    impl GameObserver for Lineup {
+        fn on_event(&mut self, _game: &GameState, event: &GameEvent) -> Result<(), GameError> {
+            if let GameEvent::GameStarted { red, yellow } = event { self.0.borrow_mut().extend([red.clone(), yellow.clone()]); }
+
+            return Ok(());
+        }
+    }
+
+    #[test]
+    fn undo_rewinds_to_the_players_own_turn() {
+        let red: Box<ScriptedPlayer> = Box::new(ScriptedPlayer { turns: VecDeque::from([Turn::Move(3), Turn::Move(2), Turn::Undo, Turn::Move(4)]) });
+        let mut controller: GameController = GameController::new(red, Box::new(RandomPlayer::with_seed(1)));
+
+        for _ in 0..4 { controller.play_turn().unwrap(); }
+        assert_eq!(controller.game().moves().len(), 4);
+
+        // red's undo takes back yellow's reply and red's own move
+        controller.play_turn().unwrap();
+        assert_eq!(controller.game().moves().len(), 2);
+        assert_eq!(controller.game().current_piece(), board::RED_PIECE);
+
+        controller.play_turn().unwrap();
+        assert_eq!(controller.game().moves()[2], 4);
+
+        // yellow's last move was the second; red's third goes with it
+        assert_eq!(controller.undo_for(board::YELLOW_PIECE).unwrap(), 2);
+        assert_eq!(controller.game().current_piece(), board::YELLOW_PIECE);
+
+        assert_eq!(controller.undo_for(board::YELLOW_PIECE).unwrap(), 0);
+        assert_eq!(controller.undo_for(board::RED_PIECE).unwrap(), 1);
+        assert!(controller.game().moves().is_empty());
+    }
+
+    #[test]
+    fn four_in_a_column_wins_for_a_remote_player() {
+        let mut sent: Vec<u8> = Vec::new();
+        let remote: RemotePlayer<&[u8], &mut Vec<u8>> = RemotePlayer::new(b"a\nA\nA\nA\n", &mut sent);
+
+        let mut controller: GameController = GameController::new(Box::new(remote), ScriptedPlayer::moves(&[1, 1, 1]));
+        assert_eq!(controller.run().unwrap(), GameStatus::Won(board::RED_PIECE));
+        drop(controller);
+
+        // the moves so far on each of red's turns, then the finished game
+        let lines: Vec<&str> = std::str::from_utf8(&sent).unwrap().lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[4].ends_with("A B A B A B A"), "{}", lines[4]);
+    }
+
+    #[test]
+    fn a_full_board_without_four_is_a_draw() {
+        let moves: Vec<Column> = "F E F A G C E F F A E B B A E F G F D B B C C G C G G D G C A D A D D E D B E C B A".split(' ').map(|letter| (letter.as_bytes()[0] - b'A') as usize).collect();
+        let red: Vec<Column> = moves.iter().step_by(2).copied().collect();
+        let yellow: Vec<Column> = moves.iter().skip(1).step_by(2).copied().collect();
+
+        let mut controller: GameController = GameController::new(ScriptedPlayer::moves(&red), ScriptedPlayer::moves(&yellow));
+
+        assert_eq!(controller.run().unwrap(), GameStatus::Draw);
+        assert_eq!(controller.game().moves(), moves);
+    }
+
+    #[test]
+    fn random_players_with_the_same_seed_play_the_same_game() {
+        let play = || {
+            let mut controller: GameController = GameController::new(Box::new(RandomPlayer::with_seed(7)), Box::new(RandomPlayer::with_seed(8)));
+            controller.run().unwrap();
+            controller.game().moves().to_vec()
+        };
+
+        assert_eq!(play(), play());
+    }
+
+    #[test]
+    fn each_engine_judges_positions_its_own_way() {
+        let threats: Evaluator = Evaluator { kind: EvaluatorKind::Threats, weights: EvalWeights::default() };
+        let red: MinimaxPlayer = MinimaxPlayer::with_tie_break(2, TieBreak::Deterministic).with_evaluator(threats);
+        let yellow: MinimaxPlayer = MinimaxPlayer::with_tie_break(2, TieBreak::Deterministic);
+
+        let lineup: Rc<RefCell<Vec<PlayerInfo>>> = Rc::new(RefCell::new(Vec::new()));
+        let mut controller: GameController = GameController::new(Box::new(red), Box::new(yellow));
+        controller.add_observer(Box::new(Lineup(Rc::clone(&lineup))));
+        assert_ne!(controller.run().unwrap(), GameStatus::InProgress);
+
+        let evaluators: Vec<EvaluatorKind> = lineup.borrow().iter().map(|player| match player {
+            PlayerInfo::Minimax { evaluator, .. } => evaluator.kind,
+            other => panic!("expected an engine, got {:?}", other),
+        }).collect();
+        assert_eq!(evaluators, [EvaluatorKind::Threats, EvaluatorKind::Patterns]);
+    }
+
+    #[test]
+    fn observers_hear_events_in_order() {
+        let names: Rc<RefCell<Vec<String>>> = Rc::new(RefCell::new(Vec::new()));
+        let red: Box<ScriptedPlayer> = Box::new(ScriptedPlayer { turns: VecDeque::from([Turn::Move(3), Turn::Undo]) });
+        let yellow: MinimaxPlayer = MinimaxPlayer::with_tie_break(1, TieBreak::Deterministic);
+
+        let mut controller: GameController = GameController::new(red, Box::new(yellow));
+        controller.add_observer(Box::new(EventNames(Rc::clone(&names))));
+        for _ in 0..3 { controller.play_turn().unwrap(); }
+
+        assert_eq!(*names.borrow(), ["game_started", "move_played", "ai_thinking", "ai_move_chosen", "move_played", "undo", "undo"]);
+    }
+}
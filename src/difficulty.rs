@@ -0,0 +1,219 @@
+use std::fmt;
+
+use crate::ai_opponent;
+use crate::board;
+use crate::mcts::Budget;
+
+// forced wins and losses are capped to this before sampling, so they still dominate without overflowing
+const FORCED_SCORE: i16 = 1000;
+
+/**
+ A named AI opponent: how far ahead it looks and how it slips up <br/>
+ Mistakes come from picking columns at random weighted by their scores (a softmax), and from sometimes not seeing the opponent's winning threat
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Persona {
+    pub name: &'static str,
+    pub depth: u16,
+    // how far from the best score a column can be and still get picked; 0 always plays the best column
+    pub temperature: f32,
+    // chance of overlooking an opponent's four-in-a-row threat (and so not blocking it)
+    pub missed_block: f32,
+    // shown when choosing a difficulty
+    pub description: &'static str,
+}
+
+pub const BEGINNER: Persona = Persona { name: "beginner", depth: 2, temperature: 6.0, missed_block: 0.4, description: "looks two moves ahead and often misses threats" };
+pub const CASUAL: Persona = Persona { name: "casual", depth: 3, temperature: 4.0, missed_block: 0.25, description: "plays sensibly, with the odd blunder" };
+pub const CLUB: Persona = Persona { name: "club", depth: 5, temperature: 2.0, missed_block: 0.1, description: "a solid player who rarely gives games away" };
+pub const EXPERT: Persona = Persona { name: "expert", depth: 7, temperature: 0.5, missed_block: 0.02, description: "strong and nearly always sees your threats" };
+pub const PERFECT: Persona = Persona { name: "perfect", depth: 9, temperature: 0.0, missed_block: 0.0, description: "always plays its best move (slow on older machines)" };
+
+/** Every persona, from easiest to hardest */
+pub const PERSONAS: [Persona; 5] = [BEGINNER, CASUAL, CLUB, EXPERT, PERFECT];
+
+/**
+ How strong the AI opponent is: a named persona, one that follows the player's level, the plain engine at a fixed search depth,
+ or Monte Carlo tree search with a budget
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Difficulty {
+    Persona(Persona),
+    // blends personas to match the player's level, which is kept between sessions (see `adaptive`)
+    Adaptive,
+    // the original engine, which never makes a mistake within its depth
+    Depth(u16),
+    // plays out random games instead of searching (see `mcts`)
+    Mcts(Budget),
+}
+
+impl Difficulty {
+    /**
+     Reads a difficulty from its text form
+     * `text` - a persona's name, `adaptive`, a search depth from 1 to 9, or `mcts` with an optional budget (`mcts:5000`, `mcts:2s`)
+     */
+    pub fn parse(text: &str) -> Option<Difficulty> {
+        if let Some(persona) = find(text) { return Some(Difficulty::Persona(persona)); }
+        if text.trim().eq_ignore_ascii_case("adaptive") { return Some(Difficulty::Adaptive); }
+        if text.trim().eq_ignore_ascii_case("mcts") { return Some(Difficulty::Mcts(Budget::default())); }
+        if let Some(budget) = text.trim().to_lowercase().strip_prefix("mcts:") { return Budget::parse(budget).map(Difficulty::Mcts); }
+
+        return match text.trim().parse::<u16>() {
+            Ok(depth) if (1..=9).contains(&depth) => Some(Difficulty::Depth(depth)),
+            _ => None,
+        };
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Difficulty::Persona(persona) => write!(f, "{}", persona.name),
+            Difficulty::Adaptive => write!(f, "adaptive"),
+            Difficulty::Depth(depth) => write!(f, "{}", depth),
+            Difficulty::Mcts(budget) if *budget == Budget::default() => write!(f, "mcts"),
+            Difficulty::Mcts(budget) => write!(f, "mcts:{}", budget),
+        }
+    }
+}
+
+/**
+ Looks up a persona by name (case-insensitive)
+ * `name` - e.g. `club`
+ */
+pub fn find(name: &str) -> Option<Persona> { PERSONAS.iter().find(|persona| persona.name.eq_ignore_ascii_case(name.trim())).copied() }
+
+/** Every difficulty's name (the personas, then `adaptive` and `mcts`), for help and error messages */
+pub fn names() -> String { PERSONAS.iter().map(|persona| persona.name).chain(["adaptive", "mcts"]).collect::<Vec<&str>>().join(", ") }
+
+/**
+ Picks a column at random, each open column weighted by `exp(score / temperature)` so better columns are likelier <br/>
+ A temperature of 0 always picks the best column (nearest the centre on ties). Returns `None` if every column is full
+ * `scores` - per-column scores from the moving side's point of view, `None` for full columns
+ * `temperature` - how willing the pick is to stray from the best score
+ * `rng` - where the randomness comes from
+ */
+pub fn sample_column(scores: &[Option<i16>; board::BOARD_WIDTH], temperature: f32, rng: &mut fastrand::Rng) -> Option<usize> {
+    if temperature <= 0.0 { return ai_opponent::best_scored_column(scores); }
+
+    let values: Vec<(usize, f32)> = (0..board::BOARD_WIDTH).filter_map(|col| scores[col].map(|score| (col, score.clamp(-FORCED_SCORE, FORCED_SCORE) as f32))).collect();
+    let best: f32 = values.iter().map(|&(_, value)| value).fold(f32::MIN, f32::max);
+
+    // measured from the best score, so the weights stay between 0 and 1
+    let weights: Vec<(usize, f32)> = values.iter().map(|&(col, value)| (col, ((value - best) / temperature).exp())).collect();
+    let mut pick: f32 = rng.f32() * weights.iter().map(|&(_, weight)| weight).sum::<f32>();
+
+    for &(col, weight) in &weights {
+        if pick < weight { return Some(col); }
+        pick -= weight;
+    }
+
+    return weights.last().map(|&(col, _)| col);
+}
+
+/**
+ Could `piece` connect four with its next drop?
+ * `playing_board` - the board currently being used in the game
+ * `piece` - the piece to check for
+ */
+pub fn has_winning_drop(playing_board: &[char; board::BOARD_SIZE], piece: char) -> bool {
+    let mut scratch_board: [char; board::BOARD_SIZE] = *playing_board;
+
+    return board::get_open_columns(playing_board).into_iter().any(|col| {
+        let temp_move: (usize, usize) = board::drop_at_column(&mut scratch_board, col, piece);
+        let wins: bool = board::is_winning_board(&scratch_board, piece);
+        board::set_square_at(&mut scratch_board, temp_move.0, temp_move.1, board::EMPTY);
+
+        wins
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_opponent::TieBreak;
+    use crate::game::GameState;
+    use crate::player::{PersonaPlayer, Player};
+
+    fn game_after(moves: &[usize]) -> GameState {
+        let mut game: GameState = GameState::new();
+        for &col in moves { game.play(col).unwrap(); }
+
+        return game;
+    }
+
+    // yellow has A, B and C along the bottom, so red must block at D
+    const YELLOW_THREATENS: [usize; 6] = [6, 0, 6, 1, 5, 2];
+
+    fn searched_depth(player: &mut PersonaPlayer, game: &GameState) -> u16 {
+        player.choose_move(game).unwrap();
+
+        return player.last_search().unwrap().depth;
+    }
+
+    #[test]
+    fn personas_and_difficulties_parse_from_their_names() {
+        assert_eq!(Difficulty::parse("Club"), Some(Difficulty::Persona(CLUB)));
+        assert_eq!(Difficulty::parse(" adaptive "), Some(Difficulty::Adaptive));
+        assert_eq!(Difficulty::parse("7"), Some(Difficulty::Depth(7)));
+        assert_eq!(Difficulty::parse("mcts:2s"), Some(Difficulty::Mcts(Budget::Millis(2000))));
+
+        for text in ["0", "10", "grandmaster", "mcts:soon", ""] { assert_eq!(Difficulty::parse(text), None, "{:?}", text); }
+
+        for persona in PERSONAS {
+            let difficulty: Difficulty = Difficulty::Persona(persona);
+            assert_eq!(Difficulty::parse(&difficulty.to_string()), Some(difficulty));
+        }
+    }
+
+    #[test]
+    fn zero_temperature_always_picks_the_best_column() {
+        let scores: [Option<i16>; board::BOARD_WIDTH] = [Some(5), None, Some(-3), Some(40), Some(12), Some(40), Some(0)];
+        let mut rng: fastrand::Rng = fastrand::Rng::with_seed(1);
+
+        // D and F tie, and D is nearer the centre
+        for _ in 0..20 { assert_eq!(sample_column(&scores, 0.0, &mut rng), Some(3)); }
+        assert_eq!(sample_column(&[None; board::BOARD_WIDTH], 0.0, &mut rng), None);
+    }
+
+    #[test]
+    fn warmer_picks_stray_but_never_into_full_columns_or_past_a_forced_win() {
+        let scores: [Option<i16>; board::BOARD_WIDTH] = [Some(5), None, Some(-3), Some(40), Some(12), Some(40), Some(0)];
+        let mut rng: fastrand::Rng = fastrand::Rng::with_seed(2);
+
+        let picks: Vec<usize> = (0..500).map(|_| sample_column(&scores, 20.0, &mut rng).unwrap()).collect();
+        assert!(!picks.contains(&1));
+        assert!(picks.iter().any(|&col| col != 3 && col != 5));
+
+        let mut forced: [Option<i16>; board::BOARD_WIDTH] = [Some(0); board::BOARD_WIDTH];
+        forced[6] = Some(i16::MAX);
+        for _ in 0..100 { assert_eq!(sample_column(&forced, BEGINNER.temperature, &mut rng), Some(6)); }
+    }
+
+    #[test]
+    fn blocks_are_only_missed_when_there_is_one_to_make() {
+        let always_misses: Persona = Persona { temperature: 0.0, missed_block: 1.0, ..CASUAL };
+        let never_misses: Persona = Persona { missed_block: 0.0, ..always_misses };
+
+        let quiet: GameState = game_after(&[3, 3]);
+        assert!(!has_winning_drop(quiet.board(), board::YELLOW_PIECE));
+        assert_eq!(searched_depth(&mut PersonaPlayer::new(always_misses, TieBreak::Seeded(1)), &quiet), CASUAL.depth);
+
+        let threatened: GameState = game_after(&YELLOW_THREATENS);
+        assert!(has_winning_drop(threatened.board(), board::YELLOW_PIECE));
+        assert_eq!(searched_depth(&mut PersonaPlayer::new(always_misses, TieBreak::Seeded(1)), &threatened), 1);
+
+        let mut careful: PersonaPlayer = PersonaPlayer::new(never_misses, TieBreak::Seeded(1));
+        assert_eq!(careful.choose_move(&threatened).unwrap(), 3);
+        assert_eq!(careful.last_search().unwrap().depth, CASUAL.depth);
+    }
+
+    #[test]
+    fn threats_are_missed_at_about_the_personas_rate() {
+        let threatened: GameState = game_after(&YELLOW_THREATENS);
+        let mut player: PersonaPlayer = PersonaPlayer::new(Persona { missed_block: 0.25, ..CASUAL }, TieBreak::Seeded(42));
+
+        let missed: usize = (0..400).filter(|_| searched_depth(&mut player, &threatened) == 1).count();
+        assert!((60..=140).contains(&missed), "missed {} of 400", missed);
+    }
+}
@@ -0,0 +1,35 @@
+use std::fmt;
+use std::io;
+
+use crate::board;
+use crate::game::MoveError;
+
+/** Errors that end a console session (or a game run by `GameController`) */
+#[derive(Debug)]
+pub enum GameError {
+    // stdin was closed (or piped input ran out) while waiting for the player
+    InputClosed,
+    // the player asked to leave (ESC while choosing a column with the arrow keys)
+    Quit,
+    Io(io::Error),
+    // a player chose a column the rules don't allow (e.g. a remote player sending a full column)
+    IllegalMove(usize, MoveError),
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::InputClosed => write!(f, "input was closed, exiting"),
+            GameError::Quit => write!(f, "quit"),
+            GameError::Io(err) => write!(f, "console I/O failed: {}", err),
+            GameError::IllegalMove(col, err) if *col < board::BOARD_WIDTH => write!(f, "a player chose column {}, which isn't allowed: {}", (b'A' + *col as u8) as char, err),
+            GameError::IllegalMove(col, err) => write!(f, "a player chose column #{}, which isn't allowed: {}", col, err),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+impl From<io::Error> for GameError {
+    fn from(err: io::Error) -> Self { GameError::Io(err) }
+}
@@ -0,0 +1,210 @@
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::board;
+use crate::threats;
+
+/** The largest weight (either way) a file or the config can set; much bigger and evaluations would pile up against their limit */
+pub const MAX_WEIGHT: i16 = 1000;
+
+/**
+ What the AI's evaluation counts in a position it stops searching at (see `board::evaluate_board`) <br/>
+ Every window of four squares in a line scores one of `four`, `three` or `two` for the evaluated piece, plus `opponent_three` if the opponent is one disc off connecting there
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EvalWeights {
+    // four in a row
+    pub four: i16,
+    // three discs and an empty square
+    pub three: i16,
+    // two discs and two empty squares
+    pub two: i16,
+    // the opponent's three discs and an empty square (normally negative)
+    pub opponent_three: i16,
+    // each disc in the centre column
+    pub center: i16,
+}
+
+impl Default for EvalWeights {
+    fn default() -> Self { STANDARD.weights }
+}
+
+impl EvalWeights {
+    /**
+     Reads weights from a file laid out like the config's `[ai.weights]` table (e.g. one written by `connect_4 tune`) <br/>
+     Weights left out keep their standard values
+     * `path` - the file to read
+     */
+    pub fn load(path: &Path) -> Result<EvalWeights, String> {
+        let text: String = std::fs::read_to_string(path).map_err(|err| format!("couldn't read {}: {}", path.display(), err))?;
+        let weights: EvalWeights = toml::from_str(&text).map_err(|err| format!("{}: {}", path.display(), err.message()))?;
+
+        if weights.values().iter().any(|weight| !(-MAX_WEIGHT..=MAX_WEIGHT).contains(weight)) {
+            return Err(format!("{}: weights must be from {} to {}", path.display(), -MAX_WEIGHT, MAX_WEIGHT));
+        }
+
+        return Ok(weights);
+    }
+
+    /**
+     Writes the weights in the form `load` reads
+     * `path` - the file to write
+     * `header` - comment lines put above the weights (each starting with `#`)
+     */
+    pub fn save(&self, path: &Path, header: &str) -> io::Result<()> {
+        let text: String = toml::to_string(self).map_err(io::Error::other)?;
+
+        return std::fs::write(path, format!("{}\n{}", header, text));
+    }
+
+    /** Every weight, in the order they're declared */
+    pub fn values(&self) -> [i16; 5] { [self.four, self.three, self.two, self.opponent_three, self.center] }
+}
+
+/** Which way an AI judges the positions its search stops at */
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvaluatorKind {
+    // counts open twos and threes (`board::evaluate_board`)
+    #[default]
+    Patterns,
+    // odd/even threat theory on top of the pattern count (`threats::evaluate_threats`)
+    Threats,
+}
+
+impl EvaluatorKind {
+    /**
+     Reads an evaluator from its name
+     * `text` - `patterns` or `threats`
+     */
+    pub fn parse(text: &str) -> Option<EvaluatorKind> {
+        return match text.trim().to_lowercase().as_str() {
+            "patterns" => Some(EvaluatorKind::Patterns),
+            "threats" => Some(EvaluatorKind::Threats),
+            _ => None,
+        };
+    }
+}
+
+impl fmt::Display for EvaluatorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvaluatorKind::Patterns => write!(f, "patterns"),
+            EvaluatorKind::Threats => write!(f, "threats"),
+        }
+    }
+}
+
+/** Everything `minimax` needs to score a position once its search stops */
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Evaluator {
+    pub kind: EvaluatorKind,
+    pub weights: EvalWeights,
+}
+
+impl Evaluator {
+    /**
+     The pattern count with the given weights, i.e. a style
+     * `weights` - what each pattern is worth
+     */
+    pub fn patterns(weights: EvalWeights) -> Evaluator { Evaluator { kind: EvaluatorKind::Patterns, weights } }
+
+    /**
+     Scores a board from `piece`'s side; never `i16::MIN` or `i16::MAX`, which stand for forced results
+     * `playing_board` - the board to score
+     * `piece` - the piece to score for
+     */
+    pub fn evaluate(&self, playing_board: &[char; board::BOARD_SIZE], piece: char) -> i16 {
+        return match self.kind {
+            EvaluatorKind::Patterns => board::evaluate_board(playing_board, piece, &self.weights),
+            EvaluatorKind::Threats => threats::evaluate_threats(playing_board, piece, &self.weights),
+        };
+    }
+}
+
+/** A named set of weights, giving the AI a playing style */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Style {
+    pub name: &'static str,
+    pub weights: EvalWeights,
+    // shown in the config file and settings help
+    pub description: &'static str,
+}
+
+pub const STANDARD: Style = Style {
+    name: "standard",
+    weights: EvalWeights { four: 100, three: 5, two: 2, opponent_three: -4, center: 4 },
+    description: "the original balance",
+};
+
+pub const AGGRESSIVE: Style = Style {
+    name: "aggressive",
+    weights: EvalWeights { three: 10, two: 3, ..STANDARD.weights },
+    description: "builds its own threes over stopping yours",
+};
+
+pub const DEFENSIVE: Style = Style {
+    name: "defensive",
+    weights: EvalWeights { opponent_three: -10, ..STANDARD.weights },
+    description: "shuts down your threes before building its own",
+};
+
+pub const CENTRE_HEAVY: Style = Style {
+    name: "centre-heavy",
+    weights: EvalWeights { center: 10, ..STANDARD.weights },
+    description: "crowds the middle column",
+};
+
+/** Every style, in the order they're listed to players */
+pub const STYLES: [Style; 4] = [STANDARD, AGGRESSIVE, DEFENSIVE, CENTRE_HEAVY];
+
+/**
+ Looks up a style by name (case doesn't matter)
+ * `name` - e.g. `aggressive`
+ */
+pub fn find(name: &str) -> Option<Style> { STYLES.iter().find(|style| style.name.eq_ignore_ascii_case(name.trim())).copied() }
+
+/**
+ The style with exactly these weights, if there is one
+ * `weights` - the weights to look for
+ */
+pub fn style_of(weights: &EvalWeights) -> Option<Style> { STYLES.iter().find(|style| style.weights == *weights).copied() }
+
+/** The names of every style, for help and error messages */
+pub fn names() -> String { STYLES.iter().map(|style| style.name).collect::<Vec<&str>>().join(", ") }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn each_style_is_found_by_its_name_and_weights() {
+        assert_eq!(find("Aggressive").map(|style| style.weights.three), Some(10));
+        assert_eq!(find("defensive").map(|style| style.weights.opponent_three), Some(-10));
+        assert_eq!(find("centre-heavy").map(|style| style.weights.center), Some(10));
+        assert_eq!(find("reckless"), None);
+
+        for style in STYLES {
+            assert_eq!(find(style.name), Some(style));
+            assert_eq!(style_of(&style.weights), Some(style));
+        }
+        assert_eq!(EvalWeights::default(), STANDARD.weights);
+    }
+
+    #[test]
+    fn config_weights_adjust_the_configured_style() {
+        let config: Config = Config::parse("[ai]\nstyle = \"aggressive\"\n\n[ai.weights]\ncenter = 9\n").unwrap();
+
+        assert_eq!(config.ai_weights, EvalWeights { center: 9, ..AGGRESSIVE.weights });
+        assert_eq!(config.get("ai.style"), "custom");
+
+        let config: Config = Config::parse("[ai]\nstyle = \"defensive\"\n").unwrap();
+        assert_eq!(config.ai_weights, DEFENSIVE.weights);
+        assert_eq!(config.get("ai.style"), "defensive");
+    }
+}
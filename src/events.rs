@@ -0,0 +1,60 @@
+use std::io::Write;
+
+use serde::Serialize;
+
+use crate::error::GameError;
+use crate::game::GameState;
+use crate::player::{Column, PlayerInfo, SearchReport};
+
+/**
+ Something that happened in a game run by `GameController` <br/>
+ Together, a game's events hold everything needed to play it out again: who played, every move and every undo
+ */
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum GameEvent {
+    // red moves first
+    GameStarted { red: PlayerInfo, yellow: PlayerInfo },
+    // `number` counts from 1; (column, row) is where the piece landed
+    MovePlayed { number: usize, piece: char, column: Column, row: usize },
+    // an engine has started choosing a move for `piece`
+    AiThinking { piece: char, player: PlayerInfo },
+    // sent just before the engine's `MovePlayed`, with what its search found (if it searches)
+    AiMoveChosen { piece: char, column: Column, search: Option<SearchReport> },
+    GameWon { piece: char, moves: Vec<Column> },
+    GameDrawn { moves: Vec<Column> },
+    // move `number` was taken back, emptying (column, row) again
+    Undo { number: usize, piece: char, column: Column, row: usize },
+}
+
+/** Receives every event from a `GameController`, e.g. to draw the board, play sounds or log the game */
+pub trait GameObserver {
+    /**
+     Called after each event, once the game state already reflects it
+     * `game` - the game after the event
+     * `event` - what happened
+     */
+    fn on_event(&mut self, game: &GameState, event: &GameEvent) -> Result<(), GameError>;
+}
+
+/** Writes every event as one line of JSON, e.g. to keep a log of the games played */
+pub struct EventLog<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> EventLog<W> {
+    /**
+     Creates a log writing to `writer`
+     * `writer` - where the lines go (a file, a socket...)
+     */
+    pub fn new(writer: W) -> EventLog<W> { EventLog { writer } }
+}
+
+impl<W: Write> GameObserver for EventLog<W> {
+    fn on_event(&mut self, _game: &GameState, event: &GameEvent) -> Result<(), GameError> {
+        let line: String = serde_json::to_string(event).map_err(std::io::Error::other)?;
+
+        writeln!(self.writer, "{}", line)?;
+        return Ok(self.writer.flush()?);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::board;
+
+/** The state of a game after a move has been made */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameStatus {
+    InProgress,
+    Won(char),
+    Draw,
+}
+
+/** Reasons a move can be rejected by the rules */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MoveError {
+    GameOver,
+    OutOfRange,
+    ColumnFull,
+}
+
+impl std::fmt::Display for MoveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MoveError::GameOver => write!(f, "the game is already over"),
+            MoveError::OutOfRange => write!(f, "column is out of range"),
+            MoveError::ColumnFull => write!(f, "column is already filled"),
+        }
+    }
+}
+
+/**
+ A single game of Connect 4: the board, the moves played so far and whose turn it is <br/>
+ Red (player 1) always moves first
+ */
+#[derive(Clone, Debug)]
+pub struct GameState {
+    board: [char; board::BOARD_SIZE],
+    moves: Vec<usize>,
+    status: GameStatus,
+}
+
+impl GameState {
+    /** Creates a game with an empty board */
+    pub fn new() -> GameState {
+        GameState { board: [board::EMPTY; board::BOARD_SIZE], moves: Vec::new(), status: GameStatus::InProgress }
+    }
+
+    /** The board at its current state */
+    pub fn board(&self) -> &[char; board::BOARD_SIZE] { &self.board }
+
+    /** Every column played so far, in order */
+    pub fn moves(&self) -> &[usize] { &self.moves }
+
+    /** Whether the game is still going, won, or drawn */
+    pub fn status(&self) -> GameStatus { self.status }
+
+    /** The piece that moves next */
+    pub fn current_piece(&self) -> char {
+        if self.moves.len().is_multiple_of(2) { board::RED_PIECE } else { board::YELLOW_PIECE }
+    }
+
+    /**
+     Validates and plays a move for the current piece, returning the (column, row) the piece landed on
+     * `col` - the column to drop the piece (0 - 6)
+     */
+    pub fn play(&mut self, col: usize) -> Result<(usize, usize), MoveError> {
+        if self.status != GameStatus::InProgress { return Err(MoveError::GameOver); }
+        if col >= board::BOARD_WIDTH { return Err(MoveError::OutOfRange); }
+        if !board::is_column_open(&self.board, col) { return Err(MoveError::ColumnFull); }
+
+        let piece: char = self.current_piece();
+        let placed: (usize, usize) = board::drop_at_column(&mut self.board, col, piece);
+        self.moves.push(col);
+
+        if board::is_winning_board(&self.board, piece) {
+            self.status = GameStatus::Won(piece);
+        }
+        else if board::get_open_columns(&self.board).is_empty() {
+            self.status = GameStatus::Draw;
+        }
+
+        return Ok(placed);
+    }
+
+    /**
+     Takes back the last move (even one that ended the game), returning the (column, row) its piece was removed from <br/>
+     Returns `None` if no moves have been played
+     */
+    pub fn undo(&mut self) -> Option<(usize, usize)> {
+        let col: usize = self.moves.pop()?;
+
+        // the last piece dropped in a column is the highest one in it
+        let row: usize = (0..board::BOARD_HEIGHT).find(|&row| board::get_piece_at(&self.board, col, row) != board::EMPTY)?;
+        board::set_square_at(&mut self.board, col, row, board::EMPTY);
+        self.status = GameStatus::InProgress;
+
+        return Some((col, row));
+    }
+}
+
+impl Default for GameState {
+    fn default() -> Self { GameState::new() }
+}
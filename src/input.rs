@@ -0,0 +1,208 @@
+use std::collections::VecDeque;
+use std::io::{self, BufRead, IsTerminal};
+use std::time::Duration;
+
+use crossterm::event;
+use crossterm::terminal;
+
+use crate::error::GameError;
+
+/** Where the console game reads the player's typed lines from */
+pub trait LineInput {
+    /** Throws away anything that was typed before now (e.g. while the AI was thinking) */
+    fn discard_pending(&mut self) -> io::Result<()>;
+
+    /**
+     Reads the next typed line into `buf`, returning the number of bytes read (0 at end of input)
+     * `buf` - the string to append the line to
+     */
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize>;
+}
+
+/**
+ Reads the next line, treating the end of input as an error so prompts can't loop on it forever
+ * `input` - where to read from
+ */
+pub fn read_line(input: &mut dyn LineInput) -> Result<String, GameError> {
+    let mut line: String = String::new();
+
+    if input.read_line(&mut line)? == 0 { return Err(GameError::InputClosed); }
+
+    return Ok(line);
+}
+
+/**
+ Discards stale input, then reads the line the player types in answer to the prompt that was just shown
+ * `input` - where to read from
+ */
+pub fn read_fresh_line(input: &mut dyn LineInput) -> Result<String, GameError> {
+    input.discard_pending()?;
+
+    return read_line(input);
+}
+
+/** The real keyboard */
+#[derive(Default)]
+pub struct StdinInput;
+
+impl LineInput for StdinInput {
+    fn discard_pending(&mut self) -> io::Result<()> {
+        // piped input was written up front on purpose, so only a real keyboard gets drained
+        if !io::stdin().is_terminal() { return Ok(()); }
+
+        // in raw mode, half-typed and already-entered lines both show up as key events that can be dropped
+        terminal::enable_raw_mode()?;
+        let mut drained: io::Result<()> = Ok(());
+
+        while drained.is_ok() {
+            match event::poll(Duration::ZERO) {
+                Ok(true) => drained = event::read().map(|_| ()),
+                Ok(false) => break,
+                Err(err) => drained = Err(err),
+            }
+        }
+
+        terminal::disable_raw_mode()?;
+        return drained;
+    }
+
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> { io::stdin().read_line(buf) }
+}
+
+/**
+ Lines read from any `BufRead` (a file, a byte string in a test...) <br/>
+ Everything in it was written up front on purpose, like piped input, so nothing is ever discarded
+ */
+pub struct ReaderInput<R: BufRead> {
+    reader: R,
+}
+
+impl<R: BufRead> ReaderInput<R> {
+    /**
+     Creates input that reads lines from `reader`
+     * `reader` - where the lines come from
+     */
+    pub fn new(reader: R) -> ReaderInput<R> { ReaderInput { reader } }
+}
+
+impl<R: BufRead> LineInput for ReaderInput<R> {
+    fn discard_pending(&mut self) -> io::Result<()> { Ok(()) }
+
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> { self.reader.read_line(buf) }
+}
+
+/** A single line of scripted input and when it was typed */
+#[derive(Clone, Debug)]
+pub enum ScriptedLine {
+    // typed before the prompt appeared (while the AI was thinking, during an animation...)
+    Early(String),
+    // typed in answer to a prompt
+    Prompted(String),
+}
+
+/**
+ Input that plays back a fixed script, for tests <br/>
+ `discard_pending` drops every `Early` line at the front of the script, like the real keyboard drops keys typed too soon
+ */
+#[derive(Default)]
+pub struct ScriptedInput {
+    script: VecDeque<ScriptedLine>,
+}
+
+impl ScriptedInput {
+    /**
+     Creates input that plays back the given lines in order
+     * `script` - the lines, each marked with when it was typed
+     */
+    pub fn new(script: Vec<ScriptedLine>) -> ScriptedInput { ScriptedInput { script: script.into() } }
+
+    /** How many lines haven't been read or discarded yet */
+    pub fn remaining(&self) -> usize { self.script.len() }
+}
+
+impl LineInput for ScriptedInput {
+    fn discard_pending(&mut self) -> io::Result<()> {
+        while let Some(ScriptedLine::Early(_)) = self.script.front() {
+            self.script.pop_front();
+        }
+
+        return Ok(());
+    }
+
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        let line: String = match self.script.pop_front() {
+            Some(ScriptedLine::Early(line)) | Some(ScriptedLine::Prompted(line)) => line,
+            None => return Ok(0),
+        };
+
+        buf.push_str(&line);
+        buf.push('\n');
+
+        return Ok(line.len() + 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn early(line: &str) -> ScriptedLine { ScriptedLine::Early(String::from(line)) }
+    fn prompted(line: &str) -> ScriptedLine { ScriptedLine::Prompted(String::from(line)) }
+
+    #[test]
+    fn stale_keystrokes_are_ignored() {
+        // "A" and a held ENTER were typed while the AI was thinking
+        let mut input: ScriptedInput = ScriptedInput::new(vec![early("A"), early(""), early(""), prompted("D")]);
+
+        assert_eq!(read_fresh_line(&mut input).unwrap().trim(), "D");
+        assert_eq!(input.remaining(), 0);
+    }
+
+    #[test]
+    fn each_turn_only_sees_its_own_answer() {
+        let mut input: ScriptedInput = ScriptedInput::new(vec![
+            prompted("D"),
+            early("E"),
+            prompted("C"),
+            early(""),
+            early("G"),
+            prompted("B"),
+        ]);
+
+        let answers: Vec<String> = (0..3).map(|_| read_fresh_line(&mut input).unwrap().trim().to_string()).collect();
+
+        assert_eq!(answers, ["D", "C", "B"]);
+    }
+
+    #[test]
+    fn without_draining_stale_keystrokes_become_moves() {
+        let mut input: ScriptedInput = ScriptedInput::new(vec![early("A"), prompted("D")]);
+        let mut line: String = String::new();
+
+        input.read_line(&mut line).unwrap();
+
+        assert_eq!(line.trim(), "A");
+    }
+
+    #[test]
+    fn end_of_input_is_an_error() {
+        let mut input: ScriptedInput = ScriptedInput::new(vec![early("A")]);
+
+        assert!(matches!(read_fresh_line(&mut input), Err(GameError::InputClosed)));
+    }
+
+    #[test]
+    fn empty_line_is_not_end_of_input() {
+        let mut input: ScriptedInput = ScriptedInput::new(vec![prompted("")]);
+
+        assert_eq!(read_line(&mut input).unwrap(), "\n");
+    }
+
+    #[test]
+    fn readers_tell_an_empty_line_from_the_end_of_input() {
+        let mut input: ReaderInput<&[u8]> = ReaderInput::new(b"\n");
+
+        assert_eq!(read_line(&mut input).unwrap(), "\n");
+        assert!(matches!(read_line(&mut input), Err(GameError::InputClosed)));
+    }
+}
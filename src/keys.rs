@@ -0,0 +1,99 @@
+use std::io::{self, IsTerminal};
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+
+/** A single key press while choosing a column with the arrow keys */
+pub enum KeyInput {
+    Left,
+    Right,
+    // A - G jumps straight to a column
+    Column(usize),
+    Drop,
+    Hint,
+    ToggleAnalysis,
+    Undo,
+    Quit,
+}
+
+/**
+ Waits for the next meaningful key press in raw mode <br/>
+ Anything typed before this was called (held ENTER, key repeat, buffered newlines) is thrown away first,
+ and only presses count, so key releases on Windows can't trigger a second action
+ */
+pub fn read_key() -> io::Result<KeyInput> {
+    terminal::enable_raw_mode()?;
+
+    let key: io::Result<KeyInput> = discard_pending().and_then(|_| wait_for_key());
+
+    terminal::disable_raw_mode()?;
+    return key;
+}
+
+/**
+ Pauses for up to `duration`, returning early (with `true`) if a key is pressed <br/>
+ Without a keyboard on stdin this is just a pause
+ * `duration` - the longest time to wait
+ */
+pub fn wait_for_key_or_timeout(duration: Duration) -> io::Result<bool> {
+    if !io::stdin().is_terminal() {
+        std::thread::sleep(duration);
+        return Ok(false);
+    }
+
+    terminal::enable_raw_mode()?;
+    let pressed: io::Result<bool> = wait_for_press(Instant::now() + duration);
+    terminal::disable_raw_mode()?;
+
+    return pressed;
+}
+
+fn wait_for_press(deadline: Instant) -> io::Result<bool> {
+    loop {
+        let remaining: Duration = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() || !event::poll(remaining)? { return Ok(false); }
+
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press { return Ok(true); }
+        }
+    }
+}
+
+/** Reads and drops every event that is already waiting */
+pub fn discard_pending() -> io::Result<()> {
+    while event::poll(Duration::ZERO)? {
+        event::read()?;
+    }
+
+    return Ok(());
+}
+
+fn wait_for_key() -> io::Result<KeyInput> {
+    loop {
+        let key: KeyEvent = match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+
+        // raw mode swallows Ctrl+C, so treat it as a request to quit
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Ok(KeyInput::Quit);
+        }
+
+        match key.code {
+            KeyCode::Left => return Ok(KeyInput::Left),
+            KeyCode::Right => return Ok(KeyInput::Right),
+            KeyCode::Enter | KeyCode::Char(' ') => return Ok(KeyInput::Drop),
+            KeyCode::Esc => return Ok(KeyInput::Quit),
+            KeyCode::Char(c) => match c.to_ascii_uppercase() {
+                'A'..='G' => return Ok(KeyInput::Column(c.to_ascii_uppercase() as usize - 'A' as usize)),
+                'H' => return Ok(KeyInput::Hint),
+                'S' => return Ok(KeyInput::ToggleAnalysis),
+                'U' => return Ok(KeyInput::Undo),
+                _ => continue,
+            },
+            _ => continue,
+        }
+    }
+}
@@ -0,0 +1,26 @@
+// explicit `return` statements are the house style
+#![allow(clippy::needless_return)]
+
+pub mod board;
+pub mod ai_opponent;
+pub mod game;
+pub mod lobby;
+pub mod api;
+pub mod review;
+pub mod record;
+pub mod input;
+pub mod keys;
+pub mod error;
+pub mod presentation;
+pub mod config;
+pub mod theme;
+pub mod difficulty;
+pub mod eval;
+pub mod threats;
+pub mod mcts;
+pub mod tune;
+pub mod adaptive;
+pub mod player;
+pub mod events;
+pub mod controller;
+pub mod console;
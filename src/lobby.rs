@@ -0,0 +1,546 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::{mpsc, Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+
+use crate::board;
+use crate::game::{GameState, GameStatus};
+
+type ClientId = u64;
+
+// lines queued for a client before it counts as too slow to keep up and gets dropped
+const OUTBOX_LINES: usize = 256;
+
+/**
+ A single line sent by a client, e.g. `{"cmd":"join","game":1,"name":"ben"}` <br/>
+ Columns are zero-based (`0` = A, `6` = G)
+ */
+#[derive(Deserialize, Debug)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum Request {
+    List,
+    Create { name: String },
+    Join { game: u32, name: String },
+    Spectate { game: u32 },
+    Move { column: usize },
+    Leave,
+}
+
+/** A single line sent back to a client, tagged by `type` */
+#[derive(Serialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Response {
+    Games { games: Vec<GameSummary> },
+    Joined { game: u32, piece: char },
+    Spectating { game: u32 },
+    State {
+        game: u32,
+        board: String,
+        moves: Vec<usize>,
+        turn: char,
+        status: &'static str,
+        red: Option<String>,
+        yellow: Option<String>,
+    },
+    Left { game: u32 },
+    Error { message: String },
+}
+
+/** One entry in the reply to a `list` request */
+#[derive(Serialize, Debug)]
+pub struct GameSummary {
+    id: u32,
+    red: Option<String>,
+    yellow: Option<String>,
+    spectators: usize,
+    status: &'static str,
+}
+
+struct Seat {
+    client: ClientId,
+    name: String,
+}
+
+struct LobbyGame {
+    state: GameState,
+    red: Option<Seat>,
+    yellow: Option<Seat>,
+    spectators: Vec<ClientId>,
+    abandoned: bool,
+}
+
+impl LobbyGame {
+    /** Short status string used in both listings and state updates */
+    fn status(&self) -> &'static str {
+        if self.abandoned { return "abandoned"; }
+
+        match self.state.status() {
+            GameStatus::Won(board::RED_PIECE) => "red_won",
+            GameStatus::Won(_) => "yellow_won",
+            GameStatus::Draw => "draw",
+            GameStatus::InProgress => if self.red.is_some() && self.yellow.is_some() { "playing" } else { "waiting" },
+        }
+    }
+
+    fn is_empty(&self) -> bool { self.red.is_none() && self.yellow.is_none() && self.spectators.is_empty() }
+
+    fn members(&self) -> Vec<ClientId> {
+        let mut members: Vec<ClientId> = self.spectators.clone();
+        members.extend(self.red.iter().chain(self.yellow.iter()).map(|seat| seat.client));
+        return members;
+    }
+
+    fn state_response(&self, id: u32) -> Response {
+        Response::State {
+            game: id,
+            board: self.state.board().iter().collect(),
+            moves: self.state.moves().to_vec(),
+            turn: self.state.current_piece(),
+            status: self.status(),
+            red: self.red.as_ref().map(|seat| seat.name.clone()),
+            yellow: self.yellow.as_ref().map(|seat| seat.name.clone()),
+        }
+    }
+}
+
+struct Client {
+    outbox: mpsc::SyncSender<String>,
+    game: Option<u32>,
+}
+
+/**
+ Every game hosted by the server, plus the connected clients and which game each one is in <br/>
+ All rule checks go through `game::GameState`, so the lobby only decides who is allowed to move
+ */
+#[derive(Default)]
+pub struct Lobby {
+    next_game: u32,
+    games: BTreeMap<u32, LobbyGame>,
+    clients: HashMap<ClientId, Client>,
+    // clients whose outbox filled up, disconnected once the current request is done
+    lagging: Vec<ClientId>,
+}
+
+impl Lobby {
+    pub fn new() -> Lobby { Lobby::default() }
+
+    /**
+     Registers a client; every response for it is pushed into `outbox` as one JSON line, and a client whose outbox
+     is full is disconnected
+     * `client` - unique id of the connection
+     * `outbox` - channel the connection's writer thread reads from
+     */
+    pub fn connect(&mut self, client: ClientId, outbox: mpsc::SyncSender<String>) {
+        self.clients.insert(client, Client { outbox, game: None });
+    }
+
+    /** Removes a client, leaving whatever game it was in */
+    pub fn disconnect(&mut self, client: ClientId) {
+        self.leave(client);
+        self.clients.remove(&client);
+        self.drop_lagging();
+    }
+
+    /**
+     Applies a single request from a client
+     * `client` - the client that sent the request
+     * `request` - the parsed request
+     */
+    pub fn handle(&mut self, client: ClientId, request: Request) {
+        self.apply(client, request);
+        self.drop_lagging();
+    }
+
+    /**
+     Reports a line that couldn't be read as a request
+     * `client` - the client that sent it
+     * `error` - what was wrong with it
+     */
+    pub fn reject(&mut self, client: ClientId, error: &str) {
+        self.send_error(client, &format!("bad request: {}", error));
+        self.drop_lagging();
+    }
+
+    fn apply(&mut self, client: ClientId, request: Request) {
+        match request {
+            Request::List => {
+                let games: Vec<GameSummary> = self.games.iter().map(|(&id, game)| GameSummary {
+                    id,
+                    red: game.red.as_ref().map(|seat| seat.name.clone()),
+                    yellow: game.yellow.as_ref().map(|seat| seat.name.clone()),
+                    spectators: game.spectators.len(),
+                    status: game.status(),
+                }).collect();
+
+                self.send(client, &Response::Games { games });
+            },
+            Request::Create { name } => {
+                if self.reject_if_in_game(client) { return; }
+
+                self.next_game += 1;
+                let id: u32 = self.next_game;
+
+                self.games.insert(id, LobbyGame {
+                    state: GameState::new(),
+                    red: Some(Seat { client, name }),
+                    yellow: None,
+                    spectators: Vec::new(),
+                    abandoned: false,
+                });
+                self.set_game(client, Some(id));
+
+                self.send(client, &Response::Joined { game: id, piece: board::RED_PIECE });
+                self.broadcast(id);
+            },
+            Request::Join { game, name } => {
+                if self.reject_if_in_game(client) { return; }
+
+                let joined: Result<(), &str> = match self.games.get_mut(&game) {
+                    None => Err("no such game"),
+                    Some(lobby_game) if lobby_game.status() != "waiting" => Err("game is not open"),
+                    Some(lobby_game) => {
+                        let seat: Seat = Seat { client, name };
+
+                        if lobby_game.red.is_none() { lobby_game.red = Some(seat); }
+                        else { lobby_game.yellow = Some(seat); }
+
+                        Ok(())
+                    },
+                };
+
+                match joined {
+                    Ok(()) => {
+                        let piece: char = if self.games[&game].red.as_ref().map(|seat| seat.client) == Some(client) { board::RED_PIECE } else { board::YELLOW_PIECE };
+
+                        self.set_game(client, Some(game));
+                        self.send(client, &Response::Joined { game, piece });
+                        self.broadcast(game);
+                    },
+                    Err(message) => self.send_error(client, message),
+                }
+            },
+            Request::Spectate { game } => {
+                if self.reject_if_in_game(client) { return; }
+
+                match self.games.get_mut(&game) {
+                    None => self.send_error(client, "no such game"),
+                    Some(lobby_game) => {
+                        lobby_game.spectators.push(client);
+
+                        self.set_game(client, Some(game));
+                        self.send(client, &Response::Spectating { game });
+                        self.broadcast(game);
+                    },
+                }
+            },
+            Request::Move { column } => {
+                let id: u32 = match self.clients.get(&client).and_then(|c| c.game) {
+                    Some(id) => id,
+                    None => { self.send_error(client, "not in a game"); return; }
+                };
+
+                let lobby_game: &mut LobbyGame = self.games.get_mut(&id).expect("client points at a missing game");
+
+                let seat_piece: Option<char> = if lobby_game.red.as_ref().map(|seat| seat.client) == Some(client) { Some(board::RED_PIECE) }
+                    else if lobby_game.yellow.as_ref().map(|seat| seat.client) == Some(client) { Some(board::YELLOW_PIECE) }
+                    else { None };
+
+                let result: Result<(), String> = if seat_piece.is_none() { Err(String::from("spectators cannot move")) }
+                    else if lobby_game.abandoned { Err(String::from("the game was abandoned")) }
+                    else if lobby_game.state.status() != GameStatus::InProgress { Err(String::from("the game is over")) }
+                    else if lobby_game.red.is_none() || lobby_game.yellow.is_none() { Err(String::from("waiting for an opponent")) }
+                    else if seat_piece != Some(lobby_game.state.current_piece()) { Err(String::from("not your turn")) }
+                    else { lobby_game.state.play(column).map(|_| ()).map_err(|err| err.to_string()) };
+
+                match result {
+                    Ok(()) => self.broadcast(id),
+                    Err(message) => self.send_error(client, &message),
+                }
+            },
+            Request::Leave => {
+                match self.clients.get(&client).and_then(|c| c.game) {
+                    Some(id) => {
+                        self.leave(client);
+                        self.send(client, &Response::Left { game: id });
+                    },
+                    None => self.send_error(client, "not in a game"),
+                }
+            },
+        }
+    }
+
+    /** Takes a client out of its game; a player leaving an unfinished game abandons it */
+    fn leave(&mut self, client: ClientId) {
+        let id: u32 = match self.clients.get(&client).and_then(|c| c.game) {
+            Some(id) => id,
+            None => return,
+        };
+
+        self.set_game(client, None);
+
+        if let Some(lobby_game) = self.games.get_mut(&id) {
+            let was_player: bool = lobby_game.red.as_ref().map(|seat| seat.client) == Some(client) || lobby_game.yellow.as_ref().map(|seat| seat.client) == Some(client);
+
+            if was_player {
+                if lobby_game.state.status() == GameStatus::InProgress && !lobby_game.state.moves().is_empty() {
+                    lobby_game.abandoned = true;
+                }
+
+                if lobby_game.red.as_ref().map(|seat| seat.client) == Some(client) { lobby_game.red = None; }
+                else { lobby_game.yellow = None; }
+            }
+
+            lobby_game.spectators.retain(|&spectator| spectator != client);
+
+            if lobby_game.is_empty() { self.games.remove(&id); }
+            else { self.broadcast(id); }
+        }
+    }
+
+    /** Disconnects every client that fell too far behind, including any that fall behind on the way */
+    fn drop_lagging(&mut self) {
+        while let Some(client) = self.lagging.pop() {
+            self.leave(client);
+            self.clients.remove(&client);
+        }
+    }
+
+    /** Sends an error and returns true if the client is already seated or spectating */
+    fn reject_if_in_game(&mut self, client: ClientId) -> bool {
+        let in_game: bool = self.clients.get(&client).and_then(|c| c.game).is_some();
+        if in_game { self.send_error(client, "leave your current game first"); }
+
+        return in_game;
+    }
+
+    fn set_game(&mut self, client: ClientId, game: Option<u32>) {
+        if let Some(c) = self.clients.get_mut(&client) { c.game = game; }
+    }
+
+    /** Sends the current state of a game to both players and every spectator */
+    fn broadcast(&mut self, id: u32) {
+        if let Some(lobby_game) = self.games.get(&id) {
+            let state: Response = lobby_game.state_response(id);
+
+            for member in lobby_game.members() {
+                self.send(member, &state);
+            }
+        }
+    }
+
+    fn send(&mut self, client: ClientId, response: &Response) {
+        if let Some(c) = self.clients.get(&client) {
+            // a disconnected outbox just means the connection is already closing
+            if let Err(mpsc::TrySendError::Full(_)) = c.outbox.try_send(serde_json::to_string(response).expect("responses always serialize")) {
+                if !self.lagging.contains(&client) { self.lagging.push(client); }
+            }
+        }
+    }
+
+    fn send_error(&mut self, client: ClientId, message: &str) {
+        self.send(client, &Response::Error { message: String::from(message) });
+    }
+}
+
+/**
+ Hosts a lobby on the given address, spawning a thread per connection. Only returns if binding fails
+ * `addr` - address to listen on, e.g. `127.0.0.1:4444`
+ */
+pub fn serve(addr: &str) -> io::Result<()> {
+    let listener: TcpListener = TcpListener::bind(addr)?;
+    let lobby: Arc<Mutex<Lobby>> = Arc::new(Mutex::new(Lobby::new()));
+    let mut next_client: ClientId = 0;
+
+    println!("Lobby listening on {}", listener.local_addr()?);
+
+    for stream in listener.incoming() {
+        let stream: TcpStream = match stream {
+            Ok(stream) => stream,
+            Err(err) => { eprintln!("connection failed: {}", err); continue; }
+        };
+
+        next_client += 1;
+        let client: ClientId = next_client;
+        let lobby: Arc<Mutex<Lobby>> = Arc::clone(&lobby);
+
+        thread::spawn(move || {
+            if let Err(err) = handle_connection(stream, client, &lobby) {
+                eprintln!("client {} dropped: {}", client, err);
+            }
+
+            lock(&lobby).disconnect(client);
+        });
+    }
+
+    return Ok(());
+}
+
+/**
+ Reads requests line by line from a single client until it disconnects
+ * `stream` - the client's connection
+ * `client` - unique id of the connection
+ * `lobby` - the shared lobby
+ */
+fn handle_connection(stream: TcpStream, client: ClientId, lobby: &Mutex<Lobby>) -> io::Result<()> {
+    let (outbox, inbox) = mpsc::sync_channel::<String>(OUTBOX_LINES);
+    let mut writer: TcpStream = stream.try_clone()?;
+
+    // writer thread ends once the lobby drops this client's sender, and closes the connection so the reader below stops too
+    thread::spawn(move || {
+        for line in inbox {
+            if writeln!(writer, "{}", line).and_then(|_| writer.flush()).is_err() { break; }
+        }
+
+        let _ = writer.shutdown(Shutdown::Both);
+    });
+
+    lock(lobby).connect(client, outbox);
+
+    for line in BufReader::new(stream).lines() {
+        let line: String = line?;
+        if line.trim().is_empty() { continue; }
+
+        let mut lobby: MutexGuard<Lobby> = lock(lobby);
+
+        match serde_json::from_str::<Request>(&line) {
+            Ok(request) => lobby.handle(client, request),
+            Err(err) => lobby.reject(client, &err.to_string()),
+        }
+    }
+
+    return Ok(());
+}
+
+/**
+ Locks the shared lobby, carrying on past a client thread that panicked while holding it rather than failing every
+ connection after it
+ * `lobby` - the shared lobby
+ */
+fn lock(lobby: &Mutex<Lobby>) -> MutexGuard<'_, Lobby> { lobby.lock().unwrap_or_else(PoisonError::into_inner) }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn connect(lobby: &mut Lobby, client: ClientId) -> mpsc::Receiver<String> {
+        let (outbox, inbox) = mpsc::sync_channel::<String>(OUTBOX_LINES);
+        lobby.connect(client, outbox);
+
+        return inbox;
+    }
+
+    fn request(lobby: &mut Lobby, client: ClientId, json: &str) { lobby.handle(client, serde_json::from_str(json).unwrap()); }
+
+    /** Every line waiting in a client's outbox */
+    fn received(inbox: &mpsc::Receiver<String>) -> Vec<Value> { inbox.try_iter().map(|line| serde_json::from_str(&line).unwrap()).collect() }
+
+    /** A game with ann as red and ben as yellow, with both outboxes emptied */
+    fn game_for_two(lobby: &mut Lobby) -> (mpsc::Receiver<String>, mpsc::Receiver<String>) {
+        let (red, yellow) = (connect(lobby, 1), connect(lobby, 2));
+        request(lobby, 1, r#"{"cmd":"create","name":"ann"}"#);
+        request(lobby, 2, r#"{"cmd":"join","game":1,"name":"ben"}"#);
+        received(&red);
+        received(&yellow);
+
+        return (red, yellow);
+    }
+
+    #[test]
+    fn creating_and_joining_seats_both_players() {
+        let mut lobby: Lobby = Lobby::new();
+        let (red, yellow, late) = (connect(&mut lobby, 1), connect(&mut lobby, 2), connect(&mut lobby, 3));
+
+        request(&mut lobby, 1, r#"{"cmd":"create","name":"ann"}"#);
+        let lines: Vec<Value> = received(&red);
+        assert_eq!(lines[0]["type"], "joined");
+        assert_eq!(lines[0]["piece"], "R");
+        assert_eq!(lines[1]["status"], "waiting");
+
+        request(&mut lobby, 2, r#"{"cmd":"join","game":1,"name":"ben"}"#);
+        assert_eq!(received(&yellow)[0]["piece"], "Y");
+        let state: Value = received(&red).remove(0);
+        assert_eq!((state["status"].as_str(), state["red"].as_str(), state["yellow"].as_str()), (Some("playing"), Some("ann"), Some("ben")));
+
+        request(&mut lobby, 3, r#"{"cmd":"join","game":1,"name":"cat"}"#);
+        assert_eq!(received(&late)[0]["message"], "game is not open");
+        request(&mut lobby, 1, r#"{"cmd":"create","name":"ann"}"#);
+        assert_eq!(received(&red)[0]["message"], "leave your current game first");
+    }
+
+    #[test]
+    fn moves_are_checked_and_broadcast_to_players_and_spectators() {
+        let mut lobby: Lobby = Lobby::new();
+        let (red, yellow) = game_for_two(&mut lobby);
+        let watcher: mpsc::Receiver<String> = connect(&mut lobby, 3);
+
+        request(&mut lobby, 3, r#"{"cmd":"spectate","game":1}"#);
+        assert_eq!(received(&watcher)[0]["type"], "spectating");
+        received(&red);
+        received(&yellow);
+
+        request(&mut lobby, 2, r#"{"cmd":"move","column":3}"#);
+        assert_eq!(received(&yellow)[0]["message"], "not your turn");
+        request(&mut lobby, 3, r#"{"cmd":"move","column":3}"#);
+        assert_eq!(received(&watcher)[0]["message"], "spectators cannot move");
+        assert!(received(&red).is_empty());
+
+        request(&mut lobby, 1, r#"{"cmd":"move","column":3}"#);
+        for inbox in [&red, &yellow, &watcher] {
+            let lines: Vec<Value> = received(inbox);
+            assert_eq!(lines.len(), 1);
+            assert_eq!(lines[0]["moves"], serde_json::json!([3]));
+            assert_eq!(lines[0]["turn"], "Y");
+        }
+    }
+
+    #[test]
+    fn a_player_leaving_mid_game_abandons_it() {
+        let mut lobby: Lobby = Lobby::new();
+        let (red, yellow) = game_for_two(&mut lobby);
+        request(&mut lobby, 1, r#"{"cmd":"move","column":3}"#);
+        received(&yellow);
+
+        request(&mut lobby, 1, r#"{"cmd":"leave"}"#);
+        let lines: Vec<Value> = received(&red);
+        assert_eq!(lines.last().unwrap()["type"], "left");
+        assert_eq!(received(&yellow)[0]["status"], "abandoned");
+
+        request(&mut lobby, 2, r#"{"cmd":"move","column":3}"#);
+        assert_eq!(received(&yellow)[0]["message"], "the game was abandoned");
+    }
+
+    #[test]
+    fn disconnecting_abandons_the_game_and_empty_games_close() {
+        let mut lobby: Lobby = Lobby::new();
+        let (_red, yellow) = game_for_two(&mut lobby);
+        request(&mut lobby, 1, r#"{"cmd":"move","column":3}"#);
+        received(&yellow);
+
+        lobby.disconnect(1);
+        assert_eq!(received(&yellow)[0]["status"], "abandoned");
+
+        lobby.disconnect(2);
+        let watcher: mpsc::Receiver<String> = connect(&mut lobby, 3);
+        request(&mut lobby, 3, r#"{"cmd":"list"}"#);
+        assert_eq!(received(&watcher)[0]["games"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn clients_that_stop_reading_are_dropped() {
+        let mut lobby: Lobby = Lobby::new();
+        let (outbox, _never_read) = mpsc::sync_channel::<String>(1);
+        lobby.connect(1, outbox);
+
+        // the joined line fills the outbox, so the state update after it overflows
+        request(&mut lobby, 1, r#"{"cmd":"create","name":"ann"}"#);
+        assert!(!lobby.clients.contains_key(&1));
+
+        let other: mpsc::Receiver<String> = connect(&mut lobby, 2);
+        request(&mut lobby, 2, r#"{"cmd":"list"}"#);
+        assert_eq!(received(&other)[0]["games"], serde_json::json!([]));
+    }
+}
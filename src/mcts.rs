@@ -0,0 +1,282 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::board::{self, BOARD_SIZE, EMPTY, RED_PIECE, YELLOW_PIECE};
+use crate::threats;
+
+// how strongly UCT explores moves it knows little about; √2 is the textbook value for results between 0 and 1
+const EXPLORATION: f64 = std::f64::consts::SQRT_2;
+
+/** How long a Monte Carlo search runs */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Budget {
+    // a fixed number of playouts, so a seeded search is repeatable on any machine
+    Iterations(u32),
+    // as many playouts as fit in this many milliseconds
+    Millis(u64),
+}
+
+impl Default for Budget {
+    fn default() -> Self { Budget::Iterations(20_000) }
+}
+
+impl Budget {
+    /**
+     Reads a budget from its text form
+     * `text` - a number of iterations (`5000`), or a time in seconds or milliseconds (`2s`, `500ms`)
+     */
+    pub fn parse(text: &str) -> Option<Budget> {
+        let text: &str = text.trim();
+
+        if let Some(millis) = text.strip_suffix("ms") { return millis.parse::<u64>().ok().filter(|&millis| millis > 0).map(Budget::Millis); }
+        if let Some(seconds) = text.strip_suffix('s') { return seconds.parse::<u64>().ok().filter(|&seconds| seconds > 0).and_then(|seconds| seconds.checked_mul(1000)).map(Budget::Millis); }
+
+        return text.parse::<u32>().ok().filter(|&iterations| iterations > 0).map(Budget::Iterations);
+    }
+}
+
+impl fmt::Display for Budget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Budget::Iterations(iterations) => write!(f, "{}", iterations),
+            Budget::Millis(millis) => write!(f, "{}ms", millis),
+        }
+    }
+}
+
+/** What a Monte Carlo search found */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SearchResult {
+    // the most visited column
+    pub column: usize,
+    // share of playouts through `column` that the moving side won (draws count half)
+    pub win_rate: f64,
+    pub iterations: u32,
+    // how many moves deep the tree grew
+    pub depth: u16,
+}
+
+// one position in the search tree, reached by `piece` dropping into `column`
+struct Node {
+    parent: Option<usize>,
+    column: usize,
+    piece: char,
+    children: Vec<usize>,
+    // open columns without a child yet
+    untried: Vec<usize>,
+    visits: u32,
+    // from `piece`'s side: 1 per won playout, 0.5 per draw
+    wins: f64,
+    // `piece`'s result if this move ended the game
+    result: Option<f64>,
+}
+
+/**
+ Picks a move by Monte Carlo tree search with UCT: grows a tree of the most promising moves, finishing each new position
+ with a quick playout (taking a win or blocking one when it can, otherwise playing at random), and plays the column
+ visited most
+ * `playing_board` - the board currently being used in the game
+ * `piece` - the piece about to move
+ * `budget` - how long to search
+ * `rng` - where the playouts' randomness comes from
+ */
+pub fn search(playing_board: &[char; BOARD_SIZE], piece: char, budget: Budget, rng: &mut fastrand::Rng) -> SearchResult {
+    let started: Instant = Instant::now();
+    let mut nodes: Vec<Node> = vec![Node {
+        parent: None,
+        column: 0,
+        // the root's "move" was the opponent's, so its children are `piece`'s
+        piece: opponent(piece),
+        children: Vec::new(),
+        untried: board::get_open_columns(playing_board),
+        visits: 0,
+        wins: 0.0,
+        result: None,
+    }];
+    let mut iterations: u32 = 0;
+    let mut deepest: u16 = 0;
+
+    while !budget_spent(budget, iterations, started) {
+        let mut scratch_board: [char; BOARD_SIZE] = *playing_board;
+        let mut current: usize = 0;
+        let mut depth: u16 = 0;
+
+        // selection: follow the best UCT child until reaching a position with untried moves (or the end of a game)
+        while nodes[current].untried.is_empty() && !nodes[current].children.is_empty() && nodes[current].result.is_none() {
+            current = best_child(&nodes, current);
+            board::drop_at_column(&mut scratch_board, nodes[current].column, nodes[current].piece);
+            depth += 1;
+        }
+
+        // expansion: try one new move
+        if nodes[current].result.is_none() && !nodes[current].untried.is_empty() {
+            let pick: usize = rng.usize(0..nodes[current].untried.len());
+            let column: usize = nodes[current].untried.swap_remove(pick);
+            let mover: char = opponent(nodes[current].piece);
+
+            let result: Option<f64> = drop_and_score(&mut scratch_board, column, mover);
+            nodes.push(Node {
+                parent: Some(current),
+                column,
+                piece: mover,
+                children: Vec::new(),
+                untried: if result.is_some() { Vec::new() } else { board::get_open_columns(&scratch_board) },
+                visits: 0,
+                wins: 0.0,
+                result,
+            });
+
+            let child: usize = nodes.len() - 1;
+            nodes[current].children.push(child);
+            current = child;
+            depth += 1;
+        }
+
+        // simulation, from the side of whoever made the last move in the tree
+        let mut result: f64 = match nodes[current].result {
+            Some(result) => result,
+            None => playout(&mut scratch_board, nodes[current].piece, rng),
+        };
+
+        // backpropagation, flipping the result at each level since players alternate
+        let mut next: Option<usize> = Some(current);
+        while let Some(index) = next {
+            nodes[index].visits += 1;
+            nodes[index].wins += result;
+            result = 1.0 - result;
+            next = nodes[index].parent;
+        }
+
+        iterations += 1;
+        deepest = deepest.max(depth);
+    }
+
+    let best: usize = nodes[0].children.iter().copied().max_by_key(|&child| nodes[child].visits).expect("an unfinished game always has an open column");
+
+    return SearchResult { column: nodes[best].column, win_rate: nodes[best].wins / nodes[best].visits as f64, iterations, depth: deepest };
+}
+
+/**
+ Has the search used up its budget?
+ * `budget` - how long the search may run
+ * `iterations` - playouts so far
+ * `started` - when the search began
+ */
+fn budget_spent(budget: Budget, iterations: u32, started: Instant) -> bool {
+    // always run at least one playout, so there's a move to pick
+    if iterations == 0 { return false; }
+
+    return match budget {
+        Budget::Iterations(limit) => iterations >= limit,
+        Budget::Millis(millis) => started.elapsed() >= Duration::from_millis(millis),
+    };
+}
+
+/**
+ The child of `parent` with the best UCT score: its win rate plus a bonus that shrinks as it's visited
+ * `nodes` - the tree
+ * `parent` - the node to pick a child of
+ */
+fn best_child(nodes: &[Node], parent: usize) -> usize {
+    let log_visits: f64 = (nodes[parent].visits as f64).ln();
+    let uct = |child: usize| -> f64 {
+        let node: &Node = &nodes[child];
+        node.wins / node.visits as f64 + EXPLORATION * (log_visits / node.visits as f64).sqrt()
+    };
+
+    return nodes[parent].children.iter().copied().max_by(|&a, &b| uct(a).total_cmp(&uct(b))).expect("only called on nodes with children");
+}
+
+/**
+ Plays out the rest of a game quickly, returning the result for `last_mover` (1 a win, 0.5 a draw, 0 a loss) <br/>
+ Each move takes a win if there is one, otherwise blocks the opponent's, otherwise picks an open column at random
+ * `scratch_board` - the board to play on (left filled in)
+ * `last_mover` - the piece that made the last move
+ * `rng` - where the random moves come from
+ */
+fn playout(scratch_board: &mut [char; BOARD_SIZE], last_mover: char, rng: &mut fastrand::Rng) -> f64 {
+    let mut mover: char = opponent(last_mover);
+
+    loop {
+        let open_columns: Vec<usize> = board::get_open_columns(scratch_board);
+        if open_columns.is_empty() { return 0.5; }
+
+        let winning = |piece: char| open_columns.iter().copied().find(|&col| threats::completes_four(scratch_board, col, landing_row(scratch_board, col), piece));
+        let column: usize = winning(mover).or_else(|| winning(opponent(mover))).unwrap_or_else(|| open_columns[rng.usize(0..open_columns.len())]);
+
+        if let Some(result) = drop_and_score(scratch_board, column, mover) {
+            return if mover == last_mover { result } else { 1.0 - result };
+        }
+
+        mover = opponent(mover);
+    }
+}
+
+/**
+ Drops a piece and returns its result if that ended the game (1 a win, 0.5 a draw)
+ * `scratch_board` - the board to play on
+ * `col` - the column to drop in
+ * `piece` - the piece to drop
+ */
+fn drop_and_score(scratch_board: &mut [char; BOARD_SIZE], col: usize, piece: char) -> Option<f64> {
+    let wins: bool = threats::completes_four(scratch_board, col, landing_row(scratch_board, col), piece);
+    board::drop_at_column(scratch_board, col, piece);
+
+    if wins { return Some(1.0); }
+    if !scratch_board.contains(&EMPTY) { return Some(0.5); }
+
+    return None;
+}
+
+/**
+ The row (from 0 at the top) a disc dropped in an open column lands on
+ * `scratch_board` - the board to look at
+ * `col` - an open column
+ */
+fn landing_row(scratch_board: &[char; BOARD_SIZE], col: usize) -> usize {
+    return (0..board::BOARD_HEIGHT).rev().find(|&row| board::get_piece_at(scratch_board, col, row) == EMPTY).expect("only called on open columns");
+}
+
+fn opponent(piece: char) -> char { if piece == RED_PIECE { YELLOW_PIECE } else { RED_PIECE } }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameState;
+
+    fn board_after(moves: &[usize]) -> [char; BOARD_SIZE] {
+        let mut game: GameState = GameState::new();
+        for &col in moves { game.play(col).unwrap(); }
+
+        return *game.board();
+    }
+
+    #[test]
+    fn connects_four_when_it_can() {
+        // red has A, B and C along the bottom, yellow has stacked on A and B
+        let result: SearchResult = search(&board_after(&[0, 0, 1, 1, 2, 6]), RED_PIECE, Budget::Iterations(500), &mut fastrand::Rng::with_seed(1));
+
+        assert_eq!(result.column, 3);
+        assert!(result.win_rate > 0.9);
+    }
+
+    #[test]
+    fn blocks_the_opponents_four() {
+        let result: SearchResult = search(&board_after(&[0, 0, 1, 1, 2]), YELLOW_PIECE, Budget::Iterations(500), &mut fastrand::Rng::with_seed(1));
+
+        assert_eq!(result.column, 3);
+    }
+
+    #[test]
+    fn budgets_read_as_playouts_or_time() {
+        assert_eq!(Budget::parse("5000"), Some(Budget::Iterations(5000)));
+        assert_eq!(Budget::parse("2s"), Some(Budget::Millis(2000)));
+        assert_eq!(Budget::parse("500ms"), Some(Budget::Millis(500)));
+        assert_eq!(Budget::parse("0"), None);
+        // too many milliseconds to count
+        assert_eq!(Budget::parse("99999999999999999s"), None);
+    }
+}
@@ -0,0 +1,360 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Instant;
+
+use serde::Serialize;
+
+use crate::adaptive;
+use crate::ai_opponent::{self, TieBreak, TieBreaker};
+use crate::board;
+use crate::difficulty::{self, Difficulty, Persona};
+use crate::error::GameError;
+use crate::eval::Evaluator;
+use crate::game::GameState;
+use crate::mcts::{self, Budget};
+use crate::record::GameRecord;
+
+/** A column on the board, 0 (A) to 6 (G) */
+pub type Column = usize;
+
+/** What a player decided to do with their turn */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Turn {
+    Move(Column),
+    // take back moves until this player's last move is undone
+    Undo,
+}
+
+/** Who (or what) is playing a side, with whatever is needed to set the same player up again */
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PlayerInfo {
+    Human { name: String },
+    Minimax { depth: u16, tie_break: TieBreak, evaluator: Evaluator },
+    // adaptive personas are blends, so the settings are recorded along with the name
+    Persona { name: &'static str, depth: u16, temperature: f32, missed_block: f32, tie_break: TieBreak, evaluator: Evaluator },
+    Mcts { budget: Budget, tie_break: TieBreak },
+    Random { seed: u64 },
+    Remote,
+}
+
+impl PlayerInfo {
+    /** Is this an engine choosing moves on this machine? */
+    pub fn is_ai(&self) -> bool { matches!(self, PlayerInfo::Minimax { .. } | PlayerInfo::Persona { .. } | PlayerInfo::Mcts { .. } | PlayerInfo::Random { .. }) }
+}
+
+/** What an engine's last search found */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct SearchReport {
+    pub depth: u16,
+    // from the moving side's point of view; `i16::MAX` is a forced win, `i16::MIN` a forced loss
+    pub score: i16,
+    pub nodes: u64,
+    pub elapsed_ms: u64,
+}
+
+/**
+ Anything that can take a turn: a person at the console, an engine, or someone on the other end of a connection <br/>
+ `GameController` asks each player for a move in turn, so new kinds of player don't need their own game loop
+ */
+pub trait Player {
+    /**
+     Picks the column to drop the current piece in
+     * `game` - the game so far (`game.current_piece()` is this player's piece)
+     */
+    fn choose_move(&mut self, game: &GameState) -> Result<Column, GameError>;
+
+    /**
+     Takes a turn, which players that can ask for an undo override (everyone else just moves)
+     * `game` - the game so far
+     */
+    fn choose_turn(&mut self, game: &GameState) -> Result<Turn, GameError> { Ok(Turn::Move(self.choose_move(game)?)) }
+
+    /** Who this player is, for `GameStarted` events and logs */
+    fn info(&self) -> PlayerInfo;
+
+    /** What the search behind the last move found, for players that search */
+    fn last_search(&self) -> Option<SearchReport> { None }
+
+    /**
+     Called once the game has ended, e.g. so a remote player sees the final move
+     * `game` - the finished game
+     */
+    fn game_over(&mut self, _game: &GameState) -> Result<(), GameError> { Ok(()) }
+}
+
+/** The minimax engine from `ai_opponent`, searching a fixed number of moves ahead */
+pub struct MinimaxPlayer {
+    pub depth: u16,
+    // how positions are judged: its style, and whether it looks at threats
+    pub evaluator: Evaluator,
+    // kept so the game can be set up (and played) again exactly
+    tie_break: TieBreak,
+    tie_breaker: TieBreaker,
+    last_search: Option<SearchReport>,
+}
+
+impl MinimaxPlayer {
+    /**
+     Creates an engine player, breaking ties from a new random seed
+     * `depth` - how far the search should go (1 - 9)
+     */
+    pub fn new(depth: u16) -> MinimaxPlayer { MinimaxPlayer::with_tie_break(depth, TieBreak::random()) }
+
+    /**
+     Creates an engine player that breaks ties a given way, e.g. from a seed saved with an earlier game
+     * `depth` - how far the search should go (1 - 9)
+     * `tie_break` - a seed, or deterministic
+     */
+    pub fn with_tie_break(depth: u16, tie_break: TieBreak) -> MinimaxPlayer {
+        MinimaxPlayer { depth, evaluator: Evaluator::default(), tie_break, tie_breaker: TieBreaker::new(tie_break), last_search: None }
+    }
+
+    /**
+     The same player judging positions another way, e.g. with a named style's weights or by its threats
+     * `evaluator` - how positions are scored once the search stops
+     */
+    pub fn with_evaluator(self, evaluator: Evaluator) -> MinimaxPlayer { MinimaxPlayer { evaluator, ..self } }
+}
+
+impl Player for MinimaxPlayer {
+    fn choose_move(&mut self, game: &GameState) -> Result<Column, GameError> {
+        let mut scratch_board: [char; board::BOARD_SIZE] = *game.board();
+        let is_yellow: bool = game.current_piece() == board::YELLOW_PIECE;
+        let started: Instant = Instant::now();
+        ai_opponent::reset_nodes_searched();
+
+        // yellow is minimax's maximizing side, red its minimizing side
+        let best: (usize, i16) = ai_opponent::minimax(&mut scratch_board, self.depth.max(1), is_yellow, i16::MIN, i16::MAX, &mut self.tie_breaker, &self.evaluator);
+
+        self.last_search = Some(SearchReport {
+            depth: self.depth.max(1),
+            score: if is_yellow { best.1 } else { ai_opponent::flip_score(best.1) },
+            nodes: ai_opponent::nodes_searched(),
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        });
+
+        return Ok(best.0);
+    }
+
+    fn info(&self) -> PlayerInfo { PlayerInfo::Minimax { depth: self.depth, tie_break: self.tie_break, evaluator: self.evaluator } }
+
+    fn last_search(&self) -> Option<SearchReport> { self.last_search }
+}
+
+/**
+ An engine with a persona's flaws: it samples columns by score instead of always taking the best, and sometimes overlooks a threat <br/>
+ Its randomness comes from the tie-break seed; deterministic mode uses seed 0, so it makes the same mistakes every game
+ */
+pub struct PersonaPlayer {
+    pub persona: Persona,
+    // how positions are judged: its style, and whether it looks at threats
+    pub evaluator: Evaluator,
+    tie_break: TieBreak,
+    rng: fastrand::Rng,
+    last_search: Option<SearchReport>,
+}
+
+impl PersonaPlayer {
+    /**
+     Creates a persona player
+     * `persona` - the difficulty to play at
+     * `tie_break` - the seed for its mistakes (or deterministic)
+     */
+    pub fn new(persona: Persona, tie_break: TieBreak) -> PersonaPlayer {
+        let seed: u64 = match tie_break { TieBreak::Seeded(seed) => seed, TieBreak::Deterministic => 0 };
+
+        PersonaPlayer { persona, evaluator: Evaluator::default(), tie_break, rng: fastrand::Rng::with_seed(seed), last_search: None }
+    }
+
+    /**
+     The same player judging positions another way, e.g. with a named style's weights or by its threats
+     * `evaluator` - how positions are scored once the search stops
+     */
+    pub fn with_evaluator(self, evaluator: Evaluator) -> PersonaPlayer { PersonaPlayer { evaluator, ..self } }
+}
+
+impl Player for PersonaPlayer {
+    fn choose_move(&mut self, game: &GameState) -> Result<Column, GameError> {
+        let piece: char = game.current_piece();
+        let opp_piece: char = if piece == board::RED_PIECE { board::YELLOW_PIECE } else { board::RED_PIECE };
+        let started: Instant = Instant::now();
+        ai_opponent::reset_nodes_searched();
+
+        // overlooking a threat means only looking at its own move, so the opponent's reply goes unseen
+        let overlooks_threat: bool = difficulty::has_winning_drop(game.board(), opp_piece) && self.rng.f32() < self.persona.missed_block;
+        let depth: u16 = if overlooks_threat { 1 } else { self.persona.depth };
+
+        let scores: [Option<i16>; board::BOARD_WIDTH] = ai_opponent::score_columns_with(game.board(), depth, piece, &self.evaluator);
+        let column: Column = difficulty::sample_column(&scores, self.persona.temperature, &mut self.rng).expect("an unfinished game always has an open column");
+
+        self.last_search = Some(SearchReport {
+            depth,
+            score: scores[column].unwrap_or_default(),
+            nodes: ai_opponent::nodes_searched(),
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        });
+
+        return Ok(column);
+    }
+
+    fn info(&self) -> PlayerInfo {
+        let Persona { name, depth, temperature, missed_block, .. } = self.persona;
+
+        return PlayerInfo::Persona { name, depth, temperature, missed_block, tie_break: self.tie_break, evaluator: self.evaluator };
+    }
+
+    fn last_search(&self) -> Option<SearchReport> { self.last_search }
+}
+
+/**
+ Monte Carlo tree search (`mcts::search`): plays out thousands of quick random games instead of scoring positions, so it plays
+ differently from the minimax engines and makes a useful baseline to measure them against <br/>
+ Its playouts come from the tie-break seed; deterministic mode uses seed 0
+ */
+pub struct MctsPlayer {
+    pub budget: Budget,
+    tie_break: TieBreak,
+    rng: fastrand::Rng,
+    last_search: Option<SearchReport>,
+}
+
+impl MctsPlayer {
+    /**
+     Creates a Monte Carlo player
+     * `budget` - how long each move's search runs
+     * `tie_break` - the seed for its playouts (or deterministic)
+     */
+    pub fn new(budget: Budget, tie_break: TieBreak) -> MctsPlayer {
+        let seed: u64 = match tie_break { TieBreak::Seeded(seed) => seed, TieBreak::Deterministic => 0 };
+
+        MctsPlayer { budget, tie_break, rng: fastrand::Rng::with_seed(seed), last_search: None }
+    }
+}
+
+impl Player for MctsPlayer {
+    fn choose_move(&mut self, game: &GameState) -> Result<Column, GameError> {
+        let started: Instant = Instant::now();
+        let result: mcts::SearchResult = mcts::search(game.board(), game.current_piece(), self.budget, &mut self.rng);
+
+        self.last_search = Some(SearchReport {
+            depth: result.depth,
+            // the win rate on the same scale as the engines' scores, from -1000 (always lost) to 1000 (always won)
+            score: ((result.win_rate - 0.5) * 2000.0).round() as i16,
+            nodes: result.iterations as u64,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        });
+
+        return Ok(result.column);
+    }
+
+    fn info(&self) -> PlayerInfo { PlayerInfo::Mcts { budget: self.budget, tie_break: self.tie_break } }
+
+    fn last_search(&self) -> Option<SearchReport> { self.last_search }
+}
+
+/**
+ The AI opponent for a difficulty, as the console and the TUI set it up
+ * `difficulty` - a persona, adaptive, a search depth or Monte Carlo search
+ * `level` - the human player's adaptive level (only used by `Difficulty::Adaptive`)
+ * `tie_break` - the seed for its choices (or deterministic)
+ * `evaluator` - how the minimax engines judge positions; Monte Carlo search plays positions out instead
+ */
+pub fn ai_player(difficulty: Difficulty, level: f32, tie_break: TieBreak, evaluator: Evaluator) -> Box<dyn Player> {
+    return match difficulty {
+        Difficulty::Persona(persona) => Box::new(PersonaPlayer::new(persona, tie_break).with_evaluator(evaluator)),
+        Difficulty::Adaptive => Box::new(PersonaPlayer::new(adaptive::persona_at(level), tie_break).with_evaluator(evaluator)),
+        Difficulty::Depth(depth) => Box::new(MinimaxPlayer::with_tie_break(depth, tie_break).with_evaluator(evaluator)),
+        Difficulty::Mcts(budget) => Box::new(MctsPlayer::new(budget, tie_break)),
+    };
+}
+
+/** Drops its piece in any open column, picked at random; useful as the weakest possible opponent */
+pub struct RandomPlayer {
+    seed: u64,
+    rng: fastrand::Rng,
+}
+
+impl RandomPlayer {
+    /** Creates a player with a random seed (still reported in its `PlayerInfo`, so its games can be repeated) */
+    pub fn new() -> RandomPlayer { RandomPlayer::with_seed(fastrand::u64(..)) }
+
+    /**
+     Creates a player that makes the same choices every time for the same seed
+     * `seed` - the generator's seed
+     */
+    pub fn with_seed(seed: u64) -> RandomPlayer { RandomPlayer { seed, rng: fastrand::Rng::with_seed(seed) } }
+}
+
+impl Default for RandomPlayer {
+    fn default() -> Self { RandomPlayer::new() }
+}
+
+impl Player for RandomPlayer {
+    fn choose_move(&mut self, game: &GameState) -> Result<Column, GameError> {
+        let open_columns: Vec<usize> = board::get_open_columns(game.board());
+
+        return Ok(open_columns[self.rng.usize(..open_columns.len())]);
+    }
+
+    fn info(&self) -> PlayerInfo { PlayerInfo::Random { seed: self.seed } }
+}
+
+/**
+ A player on the other end of a connection, speaking a line-based protocol <br/>
+ Whenever it's their turn they are sent the moves so far as a record line (e.g. `moves: D D C`) and answer with one column letter.
+ When the game ends they are sent the final `moves:` line
+ */
+pub struct RemotePlayer<R: BufRead, W: Write> {
+    reader: R,
+    writer: W,
+}
+
+impl<R: BufRead, W: Write> RemotePlayer<R, W> {
+    /**
+     Creates a remote player from both halves of a connection
+     * `reader` - where the player's answers come from
+     * `writer` - where the moves so far are sent
+     */
+    pub fn new(reader: R, writer: W) -> RemotePlayer<R, W> { RemotePlayer { reader, writer } }
+
+    fn send_moves(&mut self, game: &GameState) -> io::Result<()> {
+        // the record's last line is its move list
+        let record_text: String = GameRecord { moves: game.moves().to_vec(), seed: None }.to_text();
+
+        writeln!(self.writer, "{}", record_text.lines().last().unwrap_or_default())?;
+        return self.writer.flush();
+    }
+}
+
+impl RemotePlayer<BufReader<TcpStream>, TcpStream> {
+    /**
+     Connects to a remote player over TCP
+     * `addr` - the address they are listening on, e.g. `192.168.1.20:4455`
+     */
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<RemotePlayer<BufReader<TcpStream>, TcpStream>> {
+        let stream: TcpStream = TcpStream::connect(addr)?;
+
+        return Ok(RemotePlayer::new(BufReader::new(stream.try_clone()?), stream));
+    }
+}
+
+impl<R: BufRead, W: Write> Player for RemotePlayer<R, W> {
+    fn choose_move(&mut self, game: &GameState) -> Result<Column, GameError> {
+        self.send_moves(game)?;
+
+        let mut answer: String = String::new();
+        if self.reader.read_line(&mut answer)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "the remote player disconnected").into());
+        }
+
+        return match answer.trim().to_ascii_uppercase().as_str() {
+            letter @ ("A" | "B" | "C" | "D" | "E" | "F" | "G") => Ok((letter.as_bytes()[0] - b'A') as usize),
+            other => Err(io::Error::new(io::ErrorKind::InvalidData, format!("the remote player sent '{}' instead of a column letter", other)).into()),
+        };
+    }
+
+    fn info(&self) -> PlayerInfo { PlayerInfo::Remote }
+
+    fn game_over(&mut self, game: &GameState) -> Result<(), GameError> { Ok(self.send_moves(game)?) }
+}
@@ -0,0 +1,81 @@
+
+/** How text effects, pauses, animations, colours and screen clears play in the console game */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Presentation {
+    // 1.0 = normal, 2.0 = twice as fast, 0.5 = half speed
+    pub speed: f32,
+    // skip typewriter effects, pauses and animations entirely
+    pub instant: bool,
+    // draw text and the board in colour
+    pub color: bool,
+    // clear the screen between views, rather than printing one after another
+    pub clear_screen: bool,
+}
+
+pub const MIN_SPEED: f32 = 0.1;
+pub const MAX_SPEED: f32 = 10.0;
+
+const NORMAL: Presentation = Presentation { speed: 1.0, instant: false, color: true, clear_screen: true };
+
+impl Default for Presentation {
+    fn default() -> Self { NORMAL }
+}
+
+impl Presentation {
+    /**
+     Turns a duration written for normal speed into the duration to actually use (0 in instant mode)
+     * `secs` - the duration at normal speed, in seconds
+     */
+    pub fn scale(&self, secs: f32) -> f32 {
+        if self.instant || secs <= 0.0 { return 0.0; }
+
+        return secs / self.speed.clamp(MIN_SPEED, MAX_SPEED);
+    }
+
+    /**
+     Adjusts the settings to where the output is going <br/>
+     Output that isn't a terminal (a pipe, a log file) gets plain text with no delays or screen clears
+     * `terminal` - is the output a terminal?
+     * `no_color` - has the user asked for no colour (see `no_color_requested`)?
+     */
+    pub fn for_output(self, terminal: bool, no_color: bool) -> Presentation {
+        return Presentation {
+            instant: self.instant || !terminal,
+            color: self.color && terminal && !no_color,
+            clear_screen: self.clear_screen && terminal,
+            ..self
+        };
+    }
+}
+
+/** Is the `NO_COLOR` environment variable set to something (see https://no-color.org)? */
+pub fn no_color_requested() -> bool { std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_scale_with_speed_and_vanish_when_instant() {
+        assert_eq!(Presentation::default().scale(1.5), 1.5);
+        assert_eq!(Presentation { speed: 2.0, ..NORMAL }.scale(1.5), 0.75);
+        assert_eq!(Presentation { speed: 0.5, ..NORMAL }.scale(1.5), 3.0);
+
+        // speeds outside the limits are held to them
+        assert_eq!(Presentation { speed: 1000.0, ..NORMAL }.scale(1.0), 1.0 / MAX_SPEED);
+        assert_eq!(Presentation { speed: 0.0, ..NORMAL }.scale(1.0), 1.0 / MIN_SPEED);
+
+        assert_eq!(Presentation { instant: true, ..NORMAL }.scale(1.5), 0.0);
+        assert_eq!(Presentation::default().scale(-1.0), 0.0);
+    }
+    #[test]
+    fn output_off_the_terminal_is_plain() {
+        let speedy: Presentation = Presentation { speed: 3.0, ..NORMAL };
+
+        assert_eq!(speedy.for_output(true, false), speedy);
+        assert_eq!(speedy.for_output(false, false), Presentation { speed: 3.0, instant: true, color: false, clear_screen: false });
+
+        // NO_COLOR only takes the colour away
+        assert_eq!(speedy.for_output(true, true), Presentation { color: false, ..speedy });
+    }
+}
@@ -0,0 +1,147 @@
+use std::io;
+use std::path::Path;
+
+use crate::ai_opponent::TieBreak;
+use crate::board;
+use crate::game::GameState;
+
+const HEADER: &str = "# connect_4 game record";
+
+/**
+ A saved game: the columns played, in order, and for games against the AI how it broke ties <br/>
+ Stored as plain text so tournament games can also be written down by hand, e.g. `moves: D D C E`
+ */
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GameRecord {
+    pub moves: Vec<usize>,
+    // the AI's seed (or deterministic mode), so the same game can be played again move for move
+    pub seed: Option<TieBreak>,
+}
+
+impl GameRecord {
+    /** The record in its text form, ending with the move list */
+    pub fn to_text(&self) -> String {
+        let letters: Vec<String> = self.moves.iter().map(|&col| ((b'A' + col as u8) as char).to_string()).collect();
+        let seed: String = self.seed.map_or(String::new(), |seed| format!("seed: {}\n", seed));
+
+        return format!("{}\n{}moves: {}\n", HEADER, seed, letters.join(" "));
+    }
+
+    /**
+     Parses a record, checking every move against the rules <br/>
+     Blank lines and `#` comments are skipped; a line without a `key:` is read as the move list
+     * `text` - the record in its text form
+     */
+    pub fn parse(text: &str) -> Result<GameRecord, String> {
+        let mut record: GameRecord = GameRecord::default();
+
+        for (i, line) in text.lines().enumerate() {
+            let line: &str = line.trim();
+            if line.is_empty() || line.starts_with('#') { continue; }
+
+            let (key, value) = line.split_once(':').unwrap_or(("moves", line));
+
+            match key.trim() {
+                "moves" => record.moves = parse_moves(value).map_err(|err| format!("line {}: {}", i + 1, err))?,
+                "seed" => record.seed = Some(TieBreak::parse(value).ok_or_else(|| format!("line {}: seed must be a number or \"deterministic\", got '{}'", i + 1, value.trim()))?),
+                other => return Err(format!("line {}: unknown key '{}'", i + 1, other)),
+            }
+        }
+
+        let mut game: GameState = GameState::new();
+        for (i, &col) in record.moves.iter().enumerate() {
+            game.play(col).map_err(|err| format!("move {} ({}) is illegal: {}", i + 1, (b'A' + col as u8) as char, err))?;
+        }
+
+        return Ok(record);
+    }
+
+    /**
+     Reads and parses a record from a file
+     * `path` - the file to read
+     */
+    pub fn load(path: &Path) -> Result<GameRecord, String> {
+        let text: String = std::fs::read_to_string(path).map_err(|err| format!("couldn't read {}: {}", path.display(), err))?;
+
+        return GameRecord::parse(&text);
+    }
+
+    /**
+     Writes the record to a file
+     * `path` - the file to write
+     */
+    pub fn save(&self, path: &Path) -> io::Result<()> { std::fs::write(path, self.to_text()) }
+}
+
+/**
+ Reads column letters (A - G), with or without spaces between them
+ * `value` - the move list, e.g. `D D C E` or `DDCE`
+ */
+fn parse_moves(value: &str) -> Result<Vec<usize>, String> {
+    let mut moves: Vec<usize> = Vec::new();
+
+    for letter in value.chars().filter(|c| !c.is_whitespace()) {
+        let col: usize = (letter.to_ascii_uppercase() as usize).wrapping_sub('A' as usize);
+
+        if col >= board::BOARD_WIDTH { return Err(format!("'{}' is not a column (A - G)", letter)); }
+        moves.push(col);
+    }
+
+    return Ok(moves);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     Saves a record to a temporary file and loads it back
+     * `record` - the record to save
+     * `name` - keeps the files of tests running at the same time apart
+     */
+    fn saved_and_loaded(record: &GameRecord, name: &str) -> Result<GameRecord, String> {
+        let path: std::path::PathBuf = std::env::temp_dir().join(format!("connect_4_record_{}_{}.txt", name, std::process::id()));
+        record.save(&path).unwrap();
+
+        let loaded: Result<GameRecord, String> = GameRecord::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        return loaded;
+    }
+
+    #[test]
+    fn records_survive_saving_and_loading() {
+        let against_ai: GameRecord = GameRecord { moves: vec![3, 3, 2, 4, 6], seed: Some(TieBreak::Seeded(42)) };
+        assert_eq!(against_ai.to_text(), "# connect_4 game record\nseed: 42\nmoves: D D C E G\n");
+        assert_eq!(saved_and_loaded(&against_ai, "seeded"), Ok(against_ai));
+
+        let two_players: GameRecord = GameRecord { moves: vec![0, 1, 0], seed: None };
+        assert_eq!(saved_and_loaded(&two_players, "unseeded"), Ok(two_players));
+
+        let deterministic: GameRecord = GameRecord { moves: Vec::new(), seed: Some(TieBreak::Deterministic) };
+        assert_eq!(GameRecord::parse(&deterministic.to_text()), Ok(deterministic));
+    }
+
+    #[test]
+    fn hand_written_move_lists_are_read() {
+        assert_eq!(GameRecord::parse("ddce\n").unwrap().moves, [3, 3, 2, 4]);
+        assert_eq!(GameRecord::parse("# a club game\n\nmoves: D  D\tC\n").unwrap().moves, [3, 3, 2]);
+    }
+
+    #[test]
+    fn malformed_lines_are_reported_with_their_number() {
+        assert_eq!(GameRecord::parse("# game\nmoves: D H C\n"), Err(String::from("line 2: 'H' is not a column (A - G)")));
+        assert_eq!(GameRecord::parse("moves: D1\n"), Err(String::from("line 1: '1' is not a column (A - G)")));
+        assert!(GameRecord::parse("seed: soon\nmoves: D\n").unwrap_err().starts_with("line 1: seed must be"));
+        assert_eq!(GameRecord::parse("winner: red\n"), Err(String::from("line 1: unknown key 'winner'")));
+    }
+
+    #[test]
+    fn illegal_move_sequences_are_rejected() {
+        // a seventh disc in column A
+        assert!(GameRecord::parse("moves: A A A A A A A\n").unwrap_err().starts_with("move 7 (A) is illegal"));
+
+        // red has already connected four down column A
+        assert!(GameRecord::parse("moves: A B A B A B A B\n").unwrap_err().starts_with("move 8 (B) is illegal"));
+    }
+}
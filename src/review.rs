@@ -0,0 +1,125 @@
+use crate::ai_opponent;
+use crate::board;
+use crate::game::{GameState, GameStatus};
+
+/** How a single move compares to the engine's choice */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    // as good as the engine's pick
+    Best,
+    // worse than the engine's pick, but doesn't change the outcome
+    Inaccuracy,
+    // a forced win was available and this move doesn't keep it
+    MissedWin,
+    // this move turns a win or draw into a forced loss
+    Blunder,
+}
+
+/** The engine's view of a single move in a finished game */
+#[derive(Clone, Debug)]
+pub struct MoveReview {
+    pub number: usize,
+    pub piece: char,
+    pub column: usize,
+    // the position right before the move was played
+    pub board_before: [char; board::BOARD_SIZE],
+    // scores for every column from the mover's side
+    pub scores: [Option<i16>; board::BOARD_WIDTH],
+    pub best: usize,
+    pub verdict: Verdict,
+}
+
+impl MoveReview {
+    /** The score of the move that was actually played */
+    pub fn played_score(&self) -> i16 { self.scores[self.column].unwrap_or(i16::MIN) }
+}
+
+/**
+ Re-evaluates every position of a game with the engine and grades each move
+ * `moves` - the columns played, in order (0 = A)
+ * `depth` - search depth used for each position
+ */
+pub fn review_game(moves: &[usize], depth: u16) -> Vec<MoveReview> {
+    let mut game: GameState = GameState::new();
+    let mut reviews: Vec<MoveReview> = Vec::new();
+
+    for (i, &col) in moves.iter().enumerate() {
+        let piece: char = game.current_piece();
+        let scores: [Option<i16>; board::BOARD_WIDTH] = ai_opponent::score_columns(game.board(), depth, piece);
+        let best: usize = ai_opponent::best_scored_column(&scores).unwrap_or(col);
+
+        let played: i16 = scores[col].unwrap_or(i16::MIN);
+        let best_score: i16 = scores[best].unwrap_or(i16::MIN);
+
+        let verdict: Verdict = if played >= best_score { Verdict::Best }
+            else if played == i16::MIN { Verdict::Blunder }
+            else if best_score == i16::MAX { Verdict::MissedWin }
+            else { Verdict::Inaccuracy };
+
+        reviews.push(MoveReview { number: i + 1, piece, column: col, board_before: *game.board(), scores, best, verdict });
+
+        if game.play(col).is_err() { break; }
+    }
+
+    return reviews;
+}
+
+/**
+ Short description of a move's verdict, including the better alternative when there was one
+ * `review` - the reviewed move
+ */
+pub fn describe(review: &MoveReview) -> String {
+    let best_letter: char = (b'A' + review.best as u8) as char;
+
+    return match review.verdict {
+        Verdict::Best => String::from("best move"),
+        Verdict::Inaccuracy => format!("inaccuracy, {} was stronger", best_letter),
+        Verdict::MissedWin => format!("missed win, {} wins by force", best_letter),
+        Verdict::Blunder => format!("BLUNDER, this loses by force ({} was better)", best_letter),
+    };
+}
+
+/**
+ Builds the full plain-text review: the result, then every move with its verdict and per-column scores
+ * `moves` - the columns played, in order (0 = A)
+ * `reviews` - the output of `review_game` for those moves
+ * `depth` - the search depth the review used
+ */
+pub fn format_review(moves: &[usize], reviews: &[MoveReview], depth: u16) -> String {
+    let mut game: GameState = GameState::new();
+    for &col in moves { let _ = game.play(col); }
+
+    let result: &str = match game.status() {
+        GameStatus::Won(board::RED_PIECE) => "Player 1 wins",
+        GameStatus::Won(_) => "Player 2 wins",
+        GameStatus::Draw => "Tie",
+        GameStatus::InProgress => "Unfinished",
+    };
+
+    let mut text: String = format!("Connect 4 game review (search depth {})\nResult: {}\n\n", depth, result);
+    text.push_str("  #  player    move  scores (A - G, W = forced win, L = forced loss)\n");
+
+    for review in reviews {
+        let scores: Vec<String> = review.scores.iter().map(|score| match score {
+            Some(i16::MAX) => String::from("W"),
+            Some(i16::MIN) => String::from("L"),
+            Some(score) => score.to_string(),
+            None => String::from("-"),
+        }).collect();
+
+        text.push_str(&format!(
+            "{:>3}  Player {}  {}     [{}]  {}\n",
+            review.number,
+            if review.piece == board::RED_PIECE { 1 } else { 2 },
+            (b'A' + review.column as u8) as char,
+            scores.join(" "),
+            describe(review),
+        ));
+    }
+
+    let blunders: usize = reviews.iter().filter(|review| review.verdict == Verdict::Blunder).count();
+    let missed_wins: usize = reviews.iter().filter(|review| review.verdict == Verdict::MissedWin).count();
+    text.push_str(&format!("\n{} blunder(s), {} missed win(s)\n", blunders, missed_wins));
+
+    return text;
+}
@@ -0,0 +1,175 @@
+use colored::Color;
+
+use crate::board;
+use crate::presentation::Presentation;
+
+/** How the board and its discs are drawn */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Theme {
+    pub name: &'static str,
+    // `None` leaves the terminal's own text colour
+    pub frame_color: Option<Color>,
+    pub red_color: Option<Color>,
+    pub yellow_color: Option<Color>,
+    pub red_glyph: char,
+    pub yellow_glyph: char,
+    pub empty_glyph: char,
+    // repeated along the bottom edge of the board
+    pub base_glyph: char,
+    // drawn under each column letter
+    pub arrow_glyph: char,
+    // the left and right arrow keys, as named in key prompts
+    pub arrow_keys: &'static str,
+    // draw the full-screen interface's panels with box-drawing lines (plain `+`, `-` and `|` otherwise)
+    pub box_drawing: bool,
+    // bold discs stand out more against the frame
+    pub bold_discs: bool,
+}
+
+pub const CLASSIC: Theme = Theme {
+    name: "classic",
+    frame_color: Some(Color::TrueColor { r: 36, g: 101, b: 181 }),
+    red_color: Some(Color::Red),
+    yellow_color: Some(Color::Yellow),
+    red_glyph: board::PIECE_ICON,
+    yellow_glyph: board::PIECE_ICON,
+    empty_glyph: board::EMPTY,
+    base_glyph: '‾',
+    arrow_glyph: '↓',
+    arrow_keys: "←/→",
+    box_drawing: true,
+    bold_discs: false,
+};
+
+pub const HIGH_CONTRAST: Theme = Theme {
+    name: "high-contrast",
+    frame_color: Some(Color::BrightWhite),
+    red_color: Some(Color::BrightRed),
+    yellow_color: Some(Color::BrightYellow),
+    bold_discs: true,
+    ..CLASSIC
+};
+
+// blue and orange from the Okabe-Ito palette, which stay apart under the common forms of colour blindness
+pub const COLOR_BLIND: Theme = Theme {
+    name: "color-blind",
+    frame_color: Some(Color::TrueColor { r: 150, g: 150, b: 150 }),
+    red_color: Some(Color::TrueColor { r: 230, g: 159, b: 0 }),
+    yellow_color: Some(Color::TrueColor { r: 0, g: 114, b: 178 }),
+    bold_discs: true,
+    ..CLASSIC
+};
+
+pub const MONOCHROME: Theme = Theme {
+    name: "monochrome",
+    frame_color: None,
+    red_color: None,
+    yellow_color: None,
+    red_glyph: 'X',
+    yellow_glyph: 'O',
+    ..CLASSIC
+};
+
+pub const ASCII: Theme = Theme {
+    name: "ascii",
+    base_glyph: '=',
+    arrow_glyph: 'v',
+    arrow_keys: "<-/->",
+    box_drawing: false,
+    ..MONOCHROME
+};
+
+/** Every theme, in the order they're listed to players */
+pub const THEMES: [Theme; 5] = [CLASSIC, HIGH_CONTRAST, COLOR_BLIND, MONOCHROME, ASCII];
+
+impl Default for Theme {
+    fn default() -> Self { CLASSIC }
+}
+
+impl Theme {
+    /**
+     The character drawn for a square
+     * `piece` - the piece in the square (or `board::EMPTY`)
+     */
+    pub fn glyph(&self, piece: char) -> char {
+        return match piece {
+            board::RED_PIECE => self.red_glyph,
+            board::YELLOW_PIECE => self.yellow_glyph,
+            _ => self.empty_glyph,
+        };
+    }
+
+    /**
+     The colour a square is drawn in, if any
+     * `piece` - the piece in the square (or `board::EMPTY`)
+     */
+    pub fn color(&self, piece: char) -> Option<Color> {
+        return match piece {
+            board::RED_PIECE => self.red_color,
+            board::YELLOW_PIECE => self.yellow_color,
+            _ => None,
+        };
+    }
+
+    /** The same theme without colour, falling back to `X`/`O` discs if both players' discs look alike */
+    pub fn without_color(self) -> Theme {
+        let (red_glyph, yellow_glyph) = if self.red_glyph == self.yellow_glyph { (MONOCHROME.red_glyph, MONOCHROME.yellow_glyph) } else { (self.red_glyph, self.yellow_glyph) };
+
+        return Theme { frame_color: None, red_color: None, yellow_color: None, red_glyph, yellow_glyph, ..self };
+    }
+
+    /**
+     The theme as drawn with the given presentation settings: as it is, or without colour when they turn it off
+     * `presentation` - the settings the board is shown with
+     */
+    pub fn for_presentation(self, presentation: &Presentation) -> Theme { if presentation.color { self } else { self.without_color() } }
+
+    /** Does this theme draw everything in the terminal's own colour? */
+    pub fn is_colorless(&self) -> bool { self.frame_color.is_none() && self.red_color.is_none() && self.yellow_color.is_none() }
+}
+
+/**
+ Looks up a theme by name (case doesn't matter)
+ * `name` - e.g. `classic` or `ascii`
+ */
+pub fn find(name: &str) -> Option<Theme> { THEMES.iter().find(|theme| theme.name.eq_ignore_ascii_case(name.trim())).copied() }
+
+/** The names of every theme, for help and error messages */
+pub fn names() -> String { THEMES.iter().map(|theme| theme.name).collect::<Vec<&str>>().join(", ") }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn themes_are_found_by_name() {
+        assert_eq!(find("ASCII"), Some(ASCII));
+        assert_eq!(find(" color-blind "), Some(COLOR_BLIND));
+        assert_eq!(find("sepia"), None);
+
+        for theme in THEMES { assert_eq!(find(theme.name), Some(theme)); }
+    }
+
+    #[test]
+    fn colourless_themes_tell_the_discs_apart_by_glyph() {
+        let plain: Theme = CLASSIC.without_color();
+        assert!(plain.is_colorless());
+        assert_eq!((plain.glyph(board::RED_PIECE), plain.glyph(board::YELLOW_PIECE)), ('X', 'O'));
+        assert_eq!(plain.base_glyph, CLASSIC.base_glyph);
+
+        // glyphs that already differ are kept
+        let custom: Theme = Theme { red_glyph: '#', yellow_glyph: '@', ..HIGH_CONTRAST };
+        assert_eq!(custom.without_color().glyph(board::RED_PIECE), '#');
+
+        assert_eq!(COLOR_BLIND.for_presentation(&Presentation::default()), COLOR_BLIND);
+        assert_eq!(COLOR_BLIND.for_presentation(&Presentation { color: false, ..Presentation::default() }), COLOR_BLIND.without_color());
+    }
+
+    #[test]
+    fn the_ascii_theme_draws_nothing_but_ascii() {
+        let glyphs: [char; 5] = [ASCII.red_glyph, ASCII.yellow_glyph, ASCII.empty_glyph, ASCII.base_glyph, ASCII.arrow_glyph];
+
+        assert!(glyphs.iter().all(char::is_ascii));
+        assert!(ASCII.arrow_keys.is_ascii());
+    }
+}
@@ -0,0 +1,222 @@
+use crate::board::{self, BOARD_HEIGHT, BOARD_SIZE, BOARD_WIDTH, EMPTY, RED_PIECE, YELLOW_PIECE};
+use crate::eval::EvalWeights;
+
+// a threat on the row that suits its owner: odd rows (counting from 1 at the bottom) for red, even rows for yellow
+const GOOD_THREAT: i32 = 16;
+// a threat on the other parity, which can still force moves but rarely decides the endgame
+const OTHER_THREAT: i32 = 4;
+// two of a player's threats directly above each other: once the column reaches them, one of the two gets filled
+const STACKED_THREATS: i32 = 40;
+// holding zugzwang: the other player will run out of safe moves first as the board fills up
+const ZUGZWANG: i32 = 30;
+// the player to move can connect four right now, or the other player has two places to do it and only one can be blocked
+const FORCED_WIN: i32 = 400;
+
+/**
+ A square where a player would connect four, i.e. three of their discs share a line of four with it <br/>
+ `row` counts from 1 at the bottom, the way odd/even threat theory numbers rows
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Threat {
+    pub col: usize,
+    pub row: usize,
+    pub piece: char,
+}
+
+impl Threat {
+    /** Is the threat on the row parity that wins for its owner (odd for red, even for yellow)? */
+    pub fn is_good(&self) -> bool { (self.row % 2 == 1) == (self.piece == RED_PIECE) }
+}
+
+/**
+ Finds every threat on the board for both players, column by column from the bottom up
+ * `playing_board` - the board to look at
+ */
+pub fn find_threats(playing_board: &[char; BOARD_SIZE]) -> Vec<Threat> {
+    let mut threats: Vec<Threat> = Vec::new();
+
+    for col in 0..BOARD_WIDTH {
+        for y in (0..BOARD_HEIGHT).rev() {
+            if board::get_piece_at(playing_board, col, y) != EMPTY { continue; }
+
+            for piece in [RED_PIECE, YELLOW_PIECE] {
+                if completes_four(playing_board, col, y, piece) { threats.push(Threat { col, row: BOARD_HEIGHT - y, piece }); }
+            }
+        }
+    }
+
+    return threats;
+}
+
+/**
+ The threats that can still matter: in each column, nothing above the other player's lowest threat counts, since
+ that square gets claimed (or has to be blocked) first
+ * `threats` - every threat, from `find_threats`
+ */
+pub fn live_threats(threats: &[Threat]) -> Vec<Threat> {
+    return threats.iter().copied().filter(|threat| {
+        !threats.iter().any(|other| other.col == threat.col && other.piece != threat.piece && other.row < threat.row)
+    }).collect();
+}
+
+/**
+ Evaluates a board with odd/even threat theory (after Allis) on top of the pattern count from `board::evaluate_board` <br/>
+ Threats score more on the row parity that suits their owner and more again when stacked, the player who holds zugzwang
+ gets a bonus, and a win that can't be stopped on the next move scores close to a forced win. Like `board::evaluate_board`,
+ the score never reaches `i16::MIN` or `i16::MAX`
+ * `playing_board` - the board to evaluate
+ * `piece` - the piece to evaluate for
+ * `weights` - weights for the pattern count underneath
+ */
+pub fn evaluate_threats(playing_board: &[char; BOARD_SIZE], piece: char, weights: &EvalWeights) -> i16 {
+    let threats: Vec<Threat> = live_threats(&find_threats(playing_board));
+    // from red's side until the end
+    let mut score: i32 = 0;
+
+    for threat in &threats {
+        let value: i32 = if threat.is_good() { GOOD_THREAT } else { OTHER_THREAT };
+        let stacked: bool = threats.iter().any(|above| above.piece == threat.piece && above.col == threat.col && above.row == threat.row + 1);
+        let total: i32 = value + if stacked { STACKED_THREATS } else { 0 };
+
+        score += if threat.piece == RED_PIECE { total } else { -total };
+    }
+
+    score += match zugzwang_holder(&threats) {
+        Some(RED_PIECE) => ZUGZWANG,
+        Some(_) => -ZUGZWANG,
+        None => 0,
+    };
+
+    score += match forced_winner(playing_board, &threats) {
+        Some(RED_PIECE) => FORCED_WIN,
+        Some(_) => -FORCED_WIN,
+        None => 0,
+    };
+
+    let patterns: i32 = board::evaluate_board(playing_board, piece, weights) as i32;
+    let threat_score: i32 = if piece == RED_PIECE { score } else { -score };
+
+    return (patterns + threat_score).clamp(i16::MIN as i32 + 1, i16::MAX as i32 - 1) as i16;
+}
+
+/**
+ Who holds zugzwang, judged column by column (a simplified form of Allis's rules): red with any live odd threat, since
+ only a yellow threat below it in the same column refutes it and `live_threats` has already dropped those; otherwise
+ yellow with a live even threat, as the second player gets the even squares by answering in the same column <br/>
+ `None` if neither has a threat of the right parity
+ * `threats` - the threats still in play, from `live_threats`
+ */
+fn zugzwang_holder(threats: &[Threat]) -> Option<char> {
+    let holds = |piece: char| threats.iter().any(|threat| threat.piece == piece && threat.is_good());
+
+    if holds(RED_PIECE) { return Some(RED_PIECE); }
+    if holds(YELLOW_PIECE) { return Some(YELLOW_PIECE); }
+
+    return None;
+}
+
+/**
+ A player who will connect four whatever happens next: the player to move with a threat they can drop into, or the
+ other player with two of them (only one can be blocked)
+ * `playing_board` - the board being evaluated
+ * `threats` - the threats still in play
+ */
+fn forced_winner(playing_board: &[char; BOARD_SIZE], threats: &[Threat]) -> Option<char> {
+    let discs: usize = playing_board.iter().filter(|&&square| square != EMPTY).count();
+    let to_move: char = if discs.is_multiple_of(2) { RED_PIECE } else { YELLOW_PIECE };
+    let waiting: char = if to_move == RED_PIECE { YELLOW_PIECE } else { RED_PIECE };
+
+    let playable = |piece: char| threats.iter().filter(|threat| threat.piece == piece && is_playable(playing_board, threat)).count();
+
+    if playable(to_move) > 0 { return Some(to_move); }
+    if playable(waiting) > 1 { return Some(waiting); }
+
+    return None;
+}
+
+/**
+ Can a disc be dropped on the threat's square right now?
+ * `playing_board` - the board being evaluated
+ * `threat` - the threat to check
+ */
+fn is_playable(playing_board: &[char; BOARD_SIZE], threat: &Threat) -> bool {
+    let y: usize = BOARD_HEIGHT - threat.row;
+
+    return y == BOARD_HEIGHT - 1 || board::get_piece_at(playing_board, threat.col, y + 1) != EMPTY;
+}
+
+/**
+ Would `piece` in this empty square connect four in any direction?
+ * `playing_board` - the board being evaluated
+ * `col` - the square's column
+ * `y` - the square's row, from 0 at the top like the rest of `board`
+ * `piece` - the piece that would be dropped there
+ */
+pub fn completes_four(playing_board: &[char; BOARD_SIZE], col: usize, y: usize, piece: char) -> bool {
+    // right, down, down-right and up-right; the opposite directions are walked at the same time
+    let directions: [(isize, isize); 4] = [(1, 0), (0, 1), (1, 1), (1, -1)];
+
+    return directions.iter().any(|&(dx, dy)| {
+        let run = |sign: isize| -> usize {
+            let mut count: usize = 0;
+            let (mut x, mut row): (isize, isize) = (col as isize + dx * sign, y as isize + dy * sign);
+
+            while (0..BOARD_WIDTH as isize).contains(&x) && (0..BOARD_HEIGHT as isize).contains(&row) && board::get_piece_at(playing_board, x as usize, row as usize) == piece {
+                count += 1;
+                x += dx * sign;
+                row += dy * sign;
+            }
+            count
+        };
+
+        run(1) + run(-1) >= 3
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameState;
+
+    fn board_after(moves: &[usize]) -> [char; BOARD_SIZE] {
+        let mut game: GameState = GameState::new();
+        for &col in moves { game.play(col).unwrap(); }
+
+        return *game.board();
+    }
+
+    #[test]
+    fn three_in_a_row_threatens_the_fourth_square() {
+        // red along the bottom in A, B and C, yellow on top of A and B
+        let threats: Vec<Threat> = find_threats(&board_after(&[0, 0, 1, 1, 2]));
+
+        assert_eq!(threats, [Threat { col: 3, row: 1, piece: RED_PIECE }]);
+        assert!(threats[0].is_good());
+    }
+
+    #[test]
+    fn threats_above_the_other_players_threat_are_dead() {
+        let threats: Vec<Threat> = vec![
+            Threat { col: 2, row: 2, piece: YELLOW_PIECE },
+            Threat { col: 2, row: 3, piece: RED_PIECE },
+            Threat { col: 4, row: 3, piece: RED_PIECE },
+        ];
+
+        assert_eq!(live_threats(&threats), [threats[0], threats[2]]);
+    }
+
+    #[test]
+    fn an_odd_threat_for_red_holds_zugzwang_over_even_threats_in_other_columns() {
+        // yellow's even threat is lower, but in another column, so it can't stop red claiming the odd square
+        let threats: Vec<Threat> = vec![Threat { col: 2, row: 2, piece: YELLOW_PIECE }, Threat { col: 4, row: 3, piece: RED_PIECE }];
+        assert_eq!(zugzwang_holder(&live_threats(&threats)), Some(RED_PIECE));
+
+        // below it in the same column, yellow's threat kills red's
+        let threats: Vec<Threat> = vec![Threat { col: 4, row: 2, piece: YELLOW_PIECE }, Threat { col: 4, row: 3, piece: RED_PIECE }];
+        assert_eq!(zugzwang_holder(&live_threats(&threats)), Some(YELLOW_PIECE));
+
+        // threats on the wrong parity don't count for either side
+        let threats: Vec<Threat> = vec![Threat { col: 1, row: 3, piece: YELLOW_PIECE }, Threat { col: 5, row: 2, piece: RED_PIECE }];
+        assert_eq!(zugzwang_holder(&live_threats(&threats)), None);
+    }
+}
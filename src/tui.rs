@@ -0,0 +1,784 @@
+use std::cell::RefCell;
+use std::io::{self, Stdout};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::execute;
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::symbols::border;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Wrap};
+use ratatui::{Frame, Terminal};
+
+use connect_4::adaptive::{self, SkillStore};
+use connect_4::ai_opponent::{self, TieBreak};
+use connect_4::board;
+use connect_4::config::Config;
+use connect_4::console;
+use connect_4::controller::GameController;
+use connect_4::difficulty::{self, Difficulty};
+use connect_4::eval::Evaluator;
+use connect_4::events::{EventLog, GameEvent, GameObserver};
+use connect_4::keys;
+use connect_4::mcts::Budget;
+use connect_4::player::{self, Column, Player, PlayerInfo, Turn};
+use connect_4::presentation::{self, Presentation};
+use connect_4::theme::Theme;
+use connect_4::error::GameError;
+use connect_4::game::GameState;
+
+use crate::cli;
+
+// panel borders for themes that stick to plain ASCII
+const ASCII_BORDER: border::Set = border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+/** Where the TUI is in a session */
+#[derive(PartialEq, Eq)]
+enum Phase {
+    ChoosingMode,
+    Playing,
+    Over,
+}
+
+/** What the player picked on the mode screen */
+enum Mode {
+    TwoPlayers,
+    Ai(Difficulty),
+    Quit,
+}
+
+/** The most recent search, shown in the engine panel */
+struct EngineInfo {
+    label: String,
+    column: usize,
+    depth: u16,
+    score: i16,
+    nodes: u64,
+    elapsed: Duration,
+}
+
+/** Everything the TUI draws */
+struct App {
+    game: GameState,
+    // every difficulty on offer, and the one picked with ←/→
+    difficulties: Vec<Difficulty>,
+    selected: usize,
+    // the red player's adaptive level, shown next to the adaptive difficulty
+    level: f32,
+    // what the engine panel calls the AI, e.g. "AI (club)"; `None` in a two player game
+    ai_label: Option<String>,
+    // how the AI broke ties this game, shown in the engine panel's title
+    seed: Option<TieBreak>,
+    hint_depth: u16,
+    cursor: usize,
+    last_placed: Option<(usize, usize)>,
+    engine: Option<EngineInfo>,
+    status: String,
+    phase: Phase,
+    // is a player at the keyboard choosing a column? Only then is the disc above the board drawn
+    awaiting_move: bool,
+    // per-column scores for the player at the keyboard, drawn under the board while `show_analysis` is on ([S] toggles it)
+    show_analysis: bool,
+    analysis: Option<[Option<i16>; board::BOARD_WIDTH]>,
+    // show discs falling into place, each row lasting `drop_frame`
+    animate_drops: bool,
+    drop_frame: Duration,
+    // the row the last disc is falling through while the drop animation plays
+    falling: Option<usize>,
+    red_name: String,
+    yellow_name: String,
+    // how the board is drawn, already without colour if `NO_COLOR` asks for that
+    theme: Theme,
+}
+
+impl App {
+    /**
+     A player's name from the config
+     * `piece` - the player's piece
+     */
+    fn player_name(&self, piece: char) -> &str { if piece == board::RED_PIECE { &self.red_name } else { &self.yellow_name } }
+
+    /** The status bar text while a game is in progress */
+    fn turn_prompt(&self) -> String {
+        return format!("{} to move  [{} A-G] aim  [ENTER] drop  [H] hint  [S] scores  [U] undo  [Q] quit", self.player_name(self.game.current_piece()), self.theme.arrow_keys);
+    }
+
+    /** The status bar text while choosing what to play */
+    fn mode_prompt(&self) -> String {
+        return format!("[P] two players  [{}] AI: {}  [ENTER] play it  [1-9] search depth  [Q] quit", self.theme.arrow_keys, self.difficulties[self.selected]);
+    }
+
+    /**
+     Searches for the best column for the side to move and records the search for the engine panel <br/>
+     Scores are from the searching side's point of view
+     * `label` - what the search was for
+     * `depth` - how deep to search
+     * `evaluator` - how positions are scored once the search stops
+     */
+    fn search(&mut self, label: String, depth: u16, evaluator: &Evaluator) -> usize {
+        ai_opponent::reset_nodes_searched();
+        let started: Instant = Instant::now();
+
+        let scores: [Option<i16>; board::BOARD_WIDTH] = ai_opponent::score_columns_with(self.game.board(), depth, self.game.current_piece(), evaluator);
+        let column: usize = ai_opponent::best_scored_column(&scores).expect("an unfinished game always has an open column");
+        let score: i16 = scores[column].unwrap_or_default();
+
+        self.engine = Some(EngineInfo { label, column, depth, score, nodes: ai_opponent::nodes_searched(), elapsed: started.elapsed() });
+        return column;
+    }
+
+    /** Scores every column for the side to move if the analysis is shown, or clears the scores if not */
+    fn update_analysis(&mut self) {
+        self.analysis = self.show_analysis.then(|| ai_opponent::score_columns(self.game.board(), self.hint_depth, self.game.current_piece()));
+    }
+}
+
+/** What a key press does while a player at the keyboard is choosing a column */
+#[derive(Debug, PartialEq, Eq)]
+enum TurnKey {
+    // move the cursor to this column
+    Aim(usize),
+    Play(Turn),
+    Hint,
+    ToggleAnalysis,
+    Quit,
+    Ignored,
+}
+
+/**
+ Works out what a key does on a player's turn <br/>
+ Aiming skips over full columns, and a full column can't be jumped to with its letter
+ * `playing_board` - the board the player is choosing a column on
+ * `cursor` - the column currently aimed at
+ * `key` - the key pressed
+ */
+fn turn_key(playing_board: &[char; board::BOARD_SIZE], cursor: usize, key: KeyCode) -> TurnKey {
+    return match key {
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('Q') => TurnKey::Quit,
+        KeyCode::Left => TurnKey::Aim((0..cursor).rev().find(|&col| board::is_column_open(playing_board, col)).unwrap_or(cursor)),
+        KeyCode::Right => TurnKey::Aim((cursor + 1..board::BOARD_WIDTH).find(|&col| board::is_column_open(playing_board, col)).unwrap_or(cursor)),
+        KeyCode::Enter | KeyCode::Char(' ') => TurnKey::Play(Turn::Move(cursor)),
+        KeyCode::Char('u') | KeyCode::Char('U') => TurnKey::Play(Turn::Undo),
+        KeyCode::Char('h') | KeyCode::Char('H') => TurnKey::Hint,
+        KeyCode::Char('s') | KeyCode::Char('S') => TurnKey::ToggleAnalysis,
+        KeyCode::Char(c) if ('a'..='g').contains(&c.to_ascii_lowercase()) => {
+            let col: usize = c.to_ascii_lowercase() as usize - 'a' as usize;
+            if board::is_column_open(playing_board, col) { TurnKey::Aim(col) } else { TurnKey::Ignored }
+        },
+        _ => TurnKey::Ignored,
+    };
+}
+
+/** The terminal and what's drawn on it, shared by the players at the keyboard and the view of the game */
+struct Screen<'t> {
+    terminal: RefCell<&'t mut Terminal<CrosstermBackend<Stdout>>>,
+    app: RefCell<App>,
+}
+
+impl Screen<'_> {
+    /** Redraws everything */
+    fn draw(&self) -> Result<(), GameError> {
+        let app: std::cell::Ref<App> = self.app.borrow();
+        self.terminal.borrow_mut().draw(|frame| draw(frame, &app))?;
+
+        return Ok(());
+    }
+
+    /** Waits for the player to pick two players or a difficulty for the AI (or to quit) */
+    fn choose_mode(&self) -> Result<Mode, GameError> {
+        loop {
+            self.draw()?;
+
+            let mut app: std::cell::RefMut<App> = self.app.borrow_mut();
+            let last: usize = app.difficulties.len() - 1;
+
+            match read_key()? {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('Q') => return Ok(Mode::Quit),
+                KeyCode::Char('p') | KeyCode::Char('P') => return Ok(Mode::TwoPlayers),
+                KeyCode::Char(c @ '1'..='9') => return Ok(Mode::Ai(Difficulty::Depth(c as u16 - '0' as u16))),
+                KeyCode::Enter => return Ok(Mode::Ai(app.difficulties[app.selected])),
+                KeyCode::Left => app.selected = app.selected.saturating_sub(1),
+                KeyCode::Right => app.selected = (app.selected + 1).min(last),
+                _ => continue,
+            }
+
+            app.status = app.mode_prompt();
+        }
+    }
+
+    /**
+     Lets the player at the keyboard aim with ←/→ (or A - G) and drop with ENTER or SPACE
+     * `game` - the game so far
+     */
+    fn choose_turn(&self, game: &GameState) -> Result<Turn, GameError> {
+        {
+            let mut app: std::cell::RefMut<App> = self.app.borrow_mut();
+            app.status = app.turn_prompt();
+            app.update_analysis();
+        }
+
+        // keys pressed while the AI was thinking (or the last disc was falling) mustn't drop this player's disc
+        keys::discard_pending()?;
+
+        loop {
+            self.draw()?;
+
+            let mut app: std::cell::RefMut<App> = self.app.borrow_mut();
+
+            match turn_key(game.board(), app.cursor, read_key()?) {
+                TurnKey::Aim(col) => app.cursor = col,
+                TurnKey::Play(turn) => {
+                    app.analysis = None;
+                    return Ok(turn);
+                },
+                TurnKey::Hint => {
+                    let label: String = format!("Hint for {}", app.player_name(game.current_piece()));
+                    let depth: u16 = app.hint_depth;
+                    let col: usize = app.search(label, depth, &Evaluator::default());
+                    app.status = format!("Hint: drop in column {}", col_to_letter(col));
+                },
+                TurnKey::ToggleAnalysis => {
+                    app.show_analysis = !app.show_analysis;
+                    app.update_analysis();
+                },
+                TurnKey::Quit => return Err(GameError::Quit),
+                TurnKey::Ignored => {},
+            }
+        }
+    }
+
+    /**
+     Shows the last disc falling through the empty squares above where it landed <br/>
+     Any key skips the rest of the fall
+     * `landed_row` - the row it landed on
+     */
+    fn animate_drop(&self, landed_row: usize) -> Result<(), GameError> {
+        let frame: Duration = self.app.borrow().drop_frame;
+
+        for row in 0..landed_row {
+            self.app.borrow_mut().falling = Some(row);
+            self.draw()?;
+
+            if event::poll(frame)? {
+                event::read()?;
+                break;
+            }
+        }
+
+        self.app.borrow_mut().falling = None;
+        return Ok(());
+    }
+
+    /** Waits for the player to start a new game (`true`) or quit (`false`) once a game is over */
+    fn play_again(&self) -> Result<bool, GameError> {
+        loop {
+            self.draw()?;
+
+            match read_key()? {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('Q') => return Ok(false),
+                KeyCode::Char('n') | KeyCode::Char('N') => return Ok(true),
+                _ => {},
+            }
+        }
+    }
+}
+
+/** A player at the keyboard */
+struct TuiHuman<'s, 't> {
+    screen: &'s Screen<'t>,
+    name: String,
+}
+
+impl Player for TuiHuman<'_, '_> {
+    fn choose_move(&mut self, game: &GameState) -> Result<Column, GameError> {
+        loop {
+            if let Turn::Move(col) = self.choose_turn(game)? { return Ok(col); }
+        }
+    }
+
+    fn choose_turn(&mut self, game: &GameState) -> Result<Turn, GameError> {
+        self.screen.app.borrow_mut().awaiting_move = true;
+        let turn: Result<Turn, GameError> = self.screen.choose_turn(game);
+        self.screen.app.borrow_mut().awaiting_move = false;
+
+        return turn;
+    }
+
+    fn info(&self) -> PlayerInfo { PlayerInfo::Human { name: self.name.clone() } }
+}
+
+/** Keeps the screen up to date with everything that happens in a game */
+struct TuiView<'s, 't> {
+    screen: &'s Screen<'t>,
+}
+
+impl GameObserver for TuiView<'_, '_> {
+    fn on_event(&mut self, game: &GameState, event: &GameEvent) -> Result<(), GameError> {
+        // the disc falls before the rest of the screen catches up with the move
+        if let GameEvent::MovePlayed { column, row, .. } = event {
+            let animate: bool = {
+                let mut app: std::cell::RefMut<App> = self.screen.app.borrow_mut();
+                app.game = game.clone();
+                app.last_placed = Some((*column, *row));
+                app.animate_drops
+            };
+            if animate { self.screen.animate_drop(*row)?; }
+        }
+
+        {
+            let mut app: std::cell::RefMut<App> = self.screen.app.borrow_mut();
+            app.game = game.clone();
+
+            match event {
+                GameEvent::AiThinking { .. } => app.status = String::from("Thinking..."),
+                GameEvent::AiMoveChosen { column, search: Some(search), .. } => {
+                    let label: String = app.ai_label.clone().unwrap_or_default();
+                    app.engine = Some(EngineInfo { label, column: *column, depth: search.depth, score: search.score, nodes: search.nodes, elapsed: Duration::from_millis(search.elapsed_ms) });
+                },
+                // keep the cursor over an open column
+                GameEvent::MovePlayed { .. } if !board::is_column_open(game.board(), app.cursor) => {
+                    app.cursor = nearest_open_column(game.board(), app.cursor).unwrap_or(app.cursor);
+                },
+                GameEvent::Undo { .. } => app.last_placed = None,
+                GameEvent::GameWon { piece, .. } => {
+                    app.phase = Phase::Over;
+                    app.status = format!("{} wins!  [N] new game  [Q] quit", app.player_name(*piece));
+                },
+                GameEvent::GameDrawn { .. } => {
+                    app.phase = Phase::Over;
+                    app.status = String::from("Tie! No one wins!  [N] new game  [Q] quit");
+                },
+                _ => {},
+            }
+        }
+
+        return self.screen.draw();
+    }
+}
+
+/**
+ Runs the full-screen interface until the player quits
+ * `options` - game settings from the command line
+ * `config` - settings kept between launches (player names, the AI's difficulty and seed)
+ * `config_path` - where the config file is, so adaptive levels can be kept next to it
+ */
+pub fn run(options: &cli::Options, config: &Config, config_path: Option<&Path>) -> Result<(), GameError> {
+    // a panic mid-game would otherwise leave the shell in raw mode on the alternate screen
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = terminal::disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen);
+        default_hook(info);
+    }));
+
+    terminal::enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+
+    let mut terminal: Terminal<CrosstermBackend<Stdout>> = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+    let result: Result<(), GameError> = match run_app(&mut terminal, options, config, config_path) {
+        Err(GameError::Quit) => Ok(()),
+        result => result,
+    };
+
+    // always hand the terminal back, even if the session failed
+    terminal::disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+
+    return result;
+}
+
+fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, options: &cli::Options, config: &Config, config_path: Option<&Path>) -> Result<(), GameError> {
+    // the personas, then adaptive, Monte Carlo search and every search depth; the config's difficulty is picked to start with
+    let mut difficulties: Vec<Difficulty> = difficulty::PERSONAS.iter().map(|&persona| Difficulty::Persona(persona)).collect();
+    difficulties.extend([Difficulty::Adaptive, Difficulty::Mcts(Budget::default())]);
+    difficulties.extend((1..=9).map(Difficulty::Depth));
+    if let Some(difficulty) = config.ai_difficulty.filter(|difficulty| !difficulties.contains(difficulty)) { difficulties.push(difficulty); }
+    let selected: usize = config.ai_difficulty.and_then(|difficulty| difficulties.iter().position(|&other| other == difficulty)).unwrap_or(0);
+
+    let presentation: Presentation = options.presentation(config).for_output(true, presentation::no_color_requested());
+
+    let screen: Screen = Screen {
+        terminal: RefCell::new(terminal),
+        app: RefCell::new(App {
+            game: GameState::new(),
+            difficulties,
+            selected,
+            level: adaptive::START_LEVEL,
+            ai_label: None,
+            seed: None,
+            hint_depth: options.hint_depth,
+            cursor: board::BOARD_WIDTH / 2,
+            last_placed: None,
+            engine: None,
+            status: String::new(),
+            phase: Phase::ChoosingMode,
+            awaiting_move: false,
+            show_analysis: options.show_analysis,
+            analysis: None,
+            animate_drops: options.animate_drops(config),
+            drop_frame: Duration::from_secs_f32(presentation.scale(console::DROP_FRAME_SECS)),
+            falling: None,
+            red_name: config.red_name.clone(),
+            yellow_name: config.yellow_name.clone(),
+            theme: options.theme(config).for_presentation(&presentation),
+        }),
+    };
+
+    // adaptive levels live next to the config file (and only last until the TUI is closed without one)
+    let skill_path: Option<PathBuf> = config_path.map(SkillStore::path_for);
+    let mut skills: SkillStore = SkillStore::default();
+    let mut notice: Option<String> = None;
+    if let Some(path) = &skill_path {
+        match SkillStore::load(path) {
+            Ok(loaded) => skills = loaded,
+            Err(err) => notice = Some(format!("Couldn't read adaptive levels, starting fresh: {}", err)),
+        }
+    }
+
+    loop {
+        {
+            let mut app: std::cell::RefMut<App> = screen.app.borrow_mut();
+            app.level = skills.level(&config.red_name);
+            app.phase = Phase::ChoosingMode;
+            app.status = notice.take().unwrap_or_else(|| app.mode_prompt());
+        }
+
+        let difficulty: Option<Difficulty> = match screen.choose_mode()? {
+            Mode::Quit => return Ok(()),
+            Mode::TwoPlayers => None,
+            Mode::Ai(difficulty) => Some(difficulty),
+        };
+
+        let tie_break: TieBreak = options.tie_break(config);
+        start_game(&mut screen.app.borrow_mut(), difficulty, tie_break);
+
+        let red: Box<dyn Player + '_> = Box::new(TuiHuman { screen: &screen, name: config.red_name.clone() });
+        let yellow: Box<dyn Player + '_> = match difficulty {
+            None => Box::new(TuiHuman { screen: &screen, name: config.yellow_name.clone() }),
+            Some(difficulty) => player::ai_player(difficulty, skills.level(&config.red_name), tie_break, options.evaluator(config)),
+        };
+
+        let mut controller: GameController = GameController::new(red, yellow);
+        controller.add_observer(Box::new(TuiView { screen: &screen }));
+
+        // `--log <file>` appends every game's events to the file, one JSON object per line
+        if let Some(path) = &options.log_path {
+            let log_file: std::fs::File = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+            controller.add_observer(Box::new(EventLog::new(log_file)));
+        }
+
+        controller.run()?;
+
+        if difficulty == Some(Difficulty::Adaptive) {
+            let game: &GameState = controller.game();
+            let level: f32 = skills.record_game(&config.red_name, adaptive::red_result(game), adaptive::accuracy(game.moves(), board::RED_PIECE));
+            let saved: io::Result<()> = skill_path.as_deref().map_or(Ok(()), |path| skills.save(path));
+
+            let mut app: std::cell::RefMut<App> = screen.app.borrow_mut();
+            app.status = match saved {
+                Ok(()) => format!("{}  Level now {}", app.status, adaptive::describe(level)),
+                Err(err) => format!("{}  Couldn't save adaptive levels: {}", app.status, err),
+            };
+        }
+
+        if !screen.play_again()? { return Ok(()); }
+    }
+}
+
+/**
+ Clears the board for a new game
+ * `app` - what's drawn
+ * `difficulty` - the AI's difficulty, or `None` for two players
+ * `tie_break` - how the AI breaks ties this game
+ */
+fn start_game(app: &mut App, difficulty: Option<Difficulty>, tie_break: TieBreak) {
+    app.game = GameState::new();
+    app.ai_label = difficulty.map(|difficulty| format!("AI ({})", difficulty));
+    app.seed = difficulty.map(|_| tie_break);
+    app.cursor = board::BOARD_WIDTH / 2;
+    app.last_placed = None;
+    app.engine = None;
+    app.phase = Phase::Playing;
+}
+
+/** Waits for the next key press */
+fn read_key() -> io::Result<KeyCode> {
+    loop {
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press { return Ok(key.code); }
+        }
+    }
+}
+
+/** Draws the board, move list, engine panel and status bar */
+fn draw(frame: &mut Frame, app: &App) {
+    let [main_area, status_area] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+    let [board_area, side_area] = Layout::horizontal([Constraint::Length(31), Constraint::Min(24)]).areas(main_area);
+    let [moves_area, engine_area] = Layout::vertical([Constraint::Min(5), Constraint::Length(7)]).areas(side_area);
+
+    frame.render_widget(Paragraph::new(board_lines(app)).block(panel(&app.theme, " Connect 4 ")), board_area);
+    draw_moves(frame, app, moves_area);
+    draw_engine(frame, app, engine_area);
+    frame.render_widget(Paragraph::new(app.status.as_str()).style(Style::default().add_modifier(Modifier::REVERSED)), status_area);
+}
+
+/**
+ A bordered panel, drawn with box-drawing lines or plain ASCII as the theme asks
+ * `theme` - how the board is drawn
+ * `title` - shown in the top border
+ */
+fn panel<'a>(theme: &Theme, title: impl Into<Line<'a>>) -> Block<'a> {
+    return Block::bordered().border_set(if theme.box_drawing { border::PLAIN } else { ASCII_BORDER }).title(title);
+}
+
+fn board_lines(app: &App) -> Vec<Line<'static>> {
+    let theme: Theme = app.theme;
+    let frame_style: Style = color_style(theme.frame_color);
+    let mut lines: Vec<Line<'static>> = Vec::new();
+
+    // the disc waiting to be dropped
+    if app.awaiting_move {
+        lines.push(Line::from(vec![Span::raw(" ".repeat(2 + app.cursor * 4)), piece_span(&theme, app.game.current_piece(), false)]));
+    }
+    else {
+        lines.push(Line::raw(""));
+    }
+
+    lines.push(Line::raw("  A   B   C   D   E   F   G"));
+    lines.push(Line::styled("_____________________________", frame_style));
+
+    for row in 0..board::BOARD_HEIGHT {
+        let mut spans: Vec<Span<'static>> = Vec::new();
+
+        for col in 0..board::BOARD_WIDTH {
+            spans.push(Span::styled("| ", frame_style));
+            spans.push(match square_while_falling(app, col, row) {
+                Some(piece) => piece_span(&theme, piece, false),
+                None => piece_span(&theme, board::get_piece_at(app.game.board(), col, row), app.last_placed == Some((col, row))),
+            });
+            spans.push(Span::raw(" "));
+        }
+        spans.push(Span::styled("|", frame_style));
+        lines.push(Line::from(spans));
+
+        if row != board::BOARD_HEIGHT - 1 {
+            lines.push(Line::styled("|---|---|---|---|---|---|---|", frame_style));
+        }
+    }
+
+    lines.push(Line::styled(theme.base_glyph.to_string().repeat(29), frame_style));
+
+    // one score per column, lined up under the column letters
+    if let Some(scores) = app.analysis.filter(|_| app.awaiting_move) {
+        let cells: Vec<String> = scores.iter().map(|score| score.map_or(String::from("   "), |score| format!("{:^3}", score_cell(score)))).collect();
+        lines.push(Line::raw(format!(" {}", cells.join(" "))));
+    }
+
+    return lines;
+}
+
+/**
+ What's drawn in a square while the last disc falls: the disc in the row it's passing, and nothing yet where it lands <br/>
+ `None` when the square is drawn as it is on the board
+ * `app` - what's drawn
+ * `col` - the square's column
+ * `row` - the square's row
+ */
+fn square_while_falling(app: &App, col: usize, row: usize) -> Option<char> {
+    let falling_row: usize = app.falling?;
+    let (landed_col, landed_row) = app.last_placed?;
+    if col != landed_col { return None; }
+
+    let piece: char = board::get_piece_at(app.game.board(), landed_col, landed_row);
+    return match row {
+        _ if row == falling_row => Some(piece),
+        _ if row == landed_row => Some(board::EMPTY),
+        _ => None,
+    };
+}
+
+fn draw_moves(frame: &mut Frame, app: &App, area: Rect) {
+    let moves: &[usize] = app.game.moves();
+
+    // one line per round, newest at the bottom; only as many as fit
+    let mut lines: Vec<Line> = moves.chunks(2).enumerate().map(|(round, pair)| {
+        let yellow: String = pair.get(1).map(|&col| col_to_letter(col).to_string()).unwrap_or_default();
+        Line::raw(format!("{:>3}. {}   {}", round + 1, col_to_letter(pair[0]), yellow))
+    }).collect();
+
+    let visible: usize = area.height.saturating_sub(2) as usize;
+    if lines.len() > visible { lines.drain(..lines.len() - visible); }
+
+    frame.render_widget(Paragraph::new(lines).block(panel(&app.theme, " Moves ")), area);
+}
+
+fn draw_engine(frame: &mut Frame, app: &App, area: Rect) {
+    let difficulty: Difficulty = app.difficulties[app.selected];
+
+    let lines: Vec<Line> = match &app.engine {
+        // what the difficulty picked with ←/→ plays like
+        _ if app.phase == Phase::ChoosingMode => vec![Line::raw(format!("AI: {}", difficulty)), Line::raw(describe_difficulty(difficulty, app.level))],
+        Some(info) => vec![
+            Line::raw(info.label.clone()),
+            Line::raw(format!("Move:  {}", col_to_letter(info.column))),
+            Line::raw(format!("Depth: {}", info.depth)),
+            Line::raw(format!("Score: {}", score_text(info.score))),
+            Line::raw(format!("Nodes: {} in {:.2}s", info.nodes, info.elapsed.as_secs_f32())),
+        ],
+        None => vec![Line::raw("No search yet ([H] for a hint)")],
+    };
+
+    let title: String = match app.seed {
+        Some(seed) if app.phase != Phase::ChoosingMode => format!(" Engine (seed {}) ", seed),
+        _ => String::from(" Engine "),
+    };
+
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }).block(panel(&app.theme, title)), area);
+}
+
+/**
+ What a difficulty plays like, for the mode screen
+ * `difficulty` - the difficulty to describe
+ * `level` - the red player's adaptive level
+ */
+fn describe_difficulty(difficulty: Difficulty, level: f32) -> String {
+    return match difficulty {
+        Difficulty::Persona(persona) => persona.description.to_string(),
+        Difficulty::Adaptive => format!("matches your level (now {})", adaptive::describe(level)),
+        Difficulty::Depth(depth) => format!("the classic engine, searching {} moves ahead without slipping up", depth),
+        Difficulty::Mcts(budget) => format!("plays out random games instead of searching (budget {})", budget),
+    };
+}
+
+fn piece_span(theme: &Theme, piece: char, highlighted: bool) -> Span<'static> {
+    let style: Style = color_style(theme.color(piece));
+    let style: Style = if theme.bold_discs && piece != board::EMPTY { style.add_modifier(Modifier::BOLD) } else { style };
+
+    return Span::styled(theme.glyph(piece).to_string(), if highlighted { style.add_modifier(Modifier::REVERSED | Modifier::BOLD) } else { style });
+}
+
+/** The ratatui style for a theme colour (themes are written with `colored`'s colours for the console board) */
+fn color_style(color: Option<colored::Color>) -> Style {
+    let color: Color = match color {
+        None => return Style::default(),
+        Some(colored::Color::Black) => Color::Black,
+        Some(colored::Color::Red) => Color::Red,
+        Some(colored::Color::Green) => Color::Green,
+        Some(colored::Color::Yellow) => Color::Yellow,
+        Some(colored::Color::Blue) => Color::Blue,
+        Some(colored::Color::Magenta) => Color::Magenta,
+        Some(colored::Color::Cyan) => Color::Cyan,
+        Some(colored::Color::White) => Color::Gray,
+        Some(colored::Color::BrightBlack) => Color::DarkGray,
+        Some(colored::Color::BrightRed) => Color::LightRed,
+        Some(colored::Color::BrightGreen) => Color::LightGreen,
+        Some(colored::Color::BrightYellow) => Color::LightYellow,
+        Some(colored::Color::BrightBlue) => Color::LightBlue,
+        Some(colored::Color::BrightMagenta) => Color::LightMagenta,
+        Some(colored::Color::BrightCyan) => Color::LightCyan,
+        Some(colored::Color::BrightWhite) => Color::White,
+        Some(colored::Color::TrueColor { r, g, b }) => Color::Rgb(r, g, b),
+    };
+
+    return Style::default().fg(color);
+}
+
+fn nearest_open_column(playing_board: &[char; board::BOARD_SIZE], from: usize) -> Option<usize> {
+    return board::get_open_columns(playing_board).into_iter().min_by_key(|col| col.abs_diff(from));
+}
+
+/** A score short enough for the row under the board: W or L for forced results, otherwise held to two digits */
+fn score_cell(score: i16) -> String {
+    return match score {
+        i16::MAX => String::from("W"),
+        i16::MIN => String::from("L"),
+        _ => score.clamp(-99, 99).to_string(),
+    };
+}
+
+fn score_text(score: i16) -> String {
+    return match score {
+        i16::MAX => String::from("forced win"),
+        i16::MIN => String::from("forced loss"),
+        _ => score.to_string(),
+    };
+}
+
+fn col_to_letter(col: usize) -> char { (b'A' + col as u8) as char }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use connect_4::theme;
+    use ratatui::buffer::Buffer;
+    use ratatui::widgets::Widget;
+
+    fn board_after(moves: &[usize]) -> [char; board::BOARD_SIZE] {
+        let mut game: GameState = GameState::new();
+        for &col in moves { game.play(col).unwrap(); }
+
+        return *game.board();
+    }
+
+    #[test]
+    fn keys_aim_drop_undo_and_quit() {
+        let empty: [char; board::BOARD_SIZE] = [board::EMPTY; board::BOARD_SIZE];
+
+        assert_eq!(turn_key(&empty, 3, KeyCode::Left), TurnKey::Aim(2));
+        assert_eq!(turn_key(&empty, 3, KeyCode::Right), TurnKey::Aim(4));
+        assert_eq!(turn_key(&empty, 0, KeyCode::Left), TurnKey::Aim(0));
+        assert_eq!(turn_key(&empty, 6, KeyCode::Right), TurnKey::Aim(6));
+        assert_eq!(turn_key(&empty, 3, KeyCode::Char('f')), TurnKey::Aim(5));
+        assert_eq!(turn_key(&empty, 3, KeyCode::Char('A')), TurnKey::Aim(0));
+
+        assert_eq!(turn_key(&empty, 3, KeyCode::Enter), TurnKey::Play(Turn::Move(3)));
+        assert_eq!(turn_key(&empty, 5, KeyCode::Char(' ')), TurnKey::Play(Turn::Move(5)));
+        assert_eq!(turn_key(&empty, 3, KeyCode::Char('u')), TurnKey::Play(Turn::Undo));
+        assert_eq!(turn_key(&empty, 3, KeyCode::Char('h')), TurnKey::Hint);
+        assert_eq!(turn_key(&empty, 3, KeyCode::Char('S')), TurnKey::ToggleAnalysis);
+
+        assert_eq!(turn_key(&empty, 3, KeyCode::Esc), TurnKey::Quit);
+        assert_eq!(turn_key(&empty, 3, KeyCode::Char('q')), TurnKey::Quit);
+        assert_eq!(turn_key(&empty, 3, KeyCode::Char('x')), TurnKey::Ignored);
+    }
+
+    #[test]
+    fn ascii_panels_have_plain_borders() {
+        let area: Rect = Rect::new(0, 0, 12, 3);
+        let mut drawn: Buffer = Buffer::empty(area);
+        panel(&theme::ASCII, " Moves ").render(area, &mut drawn);
+
+        let top: String = (0..area.width).map(|x| drawn[(x, 0)].symbol()).collect();
+        assert_eq!(top, "+ Moves ---+");
+        assert_eq!(drawn[(0, 1)].symbol(), "|");
+        assert_eq!(drawn[(0, 0)].symbol(), "+");
+
+        let mut boxed: Buffer = Buffer::empty(area);
+        panel(&theme::CLASSIC, " Moves ").render(area, &mut boxed);
+        assert_eq!(boxed[(0, 0)].symbol(), "┌");
+    }
+
+    #[test]
+    fn aiming_skips_full_columns() {
+        // columns C and D are full
+        let playing_board: [char; board::BOARD_SIZE] = board_after(&[2, 2, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3]);
+
+        assert_eq!(turn_key(&playing_board, 4, KeyCode::Left), TurnKey::Aim(1));
+        assert_eq!(turn_key(&playing_board, 1, KeyCode::Right), TurnKey::Aim(4));
+        assert_eq!(turn_key(&playing_board, 1, KeyCode::Char('c')), TurnKey::Ignored);
+    }
+}
@@ -0,0 +1,253 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::ai_opponent::TieBreak;
+use crate::board;
+use crate::error::GameError;
+use crate::eval::{self, EvalWeights, Evaluator, EvaluatorKind};
+use crate::game::{GameState, GameStatus};
+use crate::mcts::Budget;
+use crate::player::{Column, MctsPlayer, MinimaxPlayer, Player};
+
+// SPSA step sizes: the learning rate shrinks as `LEARNING_RATE / (iteration + 1 + STABILITY)^0.602`, the standard decay
+const LEARNING_RATE: f64 = 10.0;
+const STABILITY: f64 = 30.0;
+// how far each weight is nudged either way when comparing; at least 1, so the rounded weights really differ
+const PERTURBATION: f64 = 2.0;
+// openings played each iteration (two games each), to steady the noisy comparison
+const OPENINGS_PER_ITERATION: usize = 4;
+// random moves played before the engines take over, so deterministic engines still meet in different positions
+const OPENING_PLIES: std::ops::RangeInclusive<usize> = 2..=4;
+// how often progress is reported
+const REPORT_EVERY: u32 = 25;
+
+/** How `tune` searches for better weights */
+#[derive(Clone, Debug, PartialEq)]
+pub struct TuneSettings {
+    // rounds of self-play, `OPENINGS_PER_ITERATION` pairs of games each
+    pub iterations: u32,
+    // how far both engines search in self-play games
+    pub depth: u16,
+    // openings against the starting weights (two games each) played at the end to check the result
+    pub check_openings: u32,
+    // where the random openings and nudges come from, so a run can be repeated
+    pub seed: u64,
+    pub start: EvalWeights,
+    pub out: PathBuf,
+    // how the engines being tuned judge positions
+    pub evaluator: EvaluatorKind,
+    // also measure the starting and tuned weights against Monte Carlo search with this budget
+    pub baseline: Option<Budget>,
+    // also play the tuned weights against an engine using them with this evaluator instead
+    pub rival: Option<EvaluatorKind>,
+}
+
+impl Default for TuneSettings {
+    fn default() -> Self {
+        TuneSettings {
+            iterations: 300,
+            depth: 3,
+            check_openings: 50,
+            seed: fastrand::u64(..),
+            start: EvalWeights::default(),
+            out: PathBuf::from("weights.toml"),
+            evaluator: EvaluatorKind::default(),
+            baseline: None,
+            rival: None,
+        }
+    }
+}
+
+/**
+ Tunes the evaluation weights by self-play with SPSA (simultaneous perturbation stochastic approximation), then writes them to `settings.out` <br/>
+ Each iteration nudges every weight up or down at random, plays the nudged-up engine against the nudged-down one from a few random openings
+ (once with each colour), and moves the weights towards whichever side scored better. `four` is left as it is: a board with four in a row
+ ends the search before it's ever evaluated
+ * `settings` - how long to tune, and where from
+ * `out` - where progress is reported
+ */
+pub fn tune(settings: &TuneSettings, out: &mut dyn Write) -> Result<EvalWeights, GameError> {
+    let mut rng: fastrand::Rng = fastrand::Rng::with_seed(settings.seed);
+    // three, two, opponent_three and center, kept unrounded between iterations
+    let start: EvalWeights = settings.start;
+    let mut theta: [f64; 4] = [start.three as f64, start.two as f64, start.opponent_three as f64, start.center as f64];
+
+    let judged_by = |weights: EvalWeights| Evaluator { kind: settings.evaluator, weights };
+
+    writeln!(out, "Tuning {} from {} by self-play: {} iterations at depth {} (seed {})", settings.evaluator, describe(&start), settings.iterations, settings.depth, settings.seed)?;
+
+    for iteration in 0..settings.iterations {
+        let step: f64 = LEARNING_RATE / (iteration as f64 + 1.0 + STABILITY).powf(0.602);
+        let signs: [f64; 4] = std::array::from_fn(|_| if rng.bool() { 1.0 } else { -1.0 });
+
+        let mut plus: MinimaxPlayer = engine(judged_by(weights_at(&start, std::array::from_fn(|i| theta[i] + PERTURBATION * signs[i]))), settings.depth);
+        let mut minus: MinimaxPlayer = engine(judged_by(weights_at(&start, std::array::from_fn(|i| theta[i] - PERTURBATION * signs[i]))), settings.depth);
+
+        // from -1 (the nudged-down engine won every game) to 1 (the nudged-up engine did)
+        let plus_points: f64 = (0..OPENINGS_PER_ITERATION).map(|_| play_pair(&mut plus, &mut minus, &random_opening(&mut rng))).sum();
+        let gain: f64 = plus_points / OPENINGS_PER_ITERATION as f64 - 1.0;
+
+        for i in 0..theta.len() {
+            let max: f64 = eval::MAX_WEIGHT as f64;
+            theta[i] = (theta[i] + step * gain / (PERTURBATION * signs[i])).clamp(-max, max);
+        }
+
+        if (iteration + 1) % REPORT_EVERY == 0 || iteration + 1 == settings.iterations {
+            writeln!(out, "iteration {}/{}: {}", iteration + 1, settings.iterations, describe(&weights_at(&start, theta)))?;
+        }
+    }
+
+    let tuned: EvalWeights = weights_at(&start, theta);
+
+    // a fresh set of openings, so the check isn't played on the positions the weights were tuned on
+    let openings: Vec<Vec<Column>> = (0..settings.check_openings).map(|_| random_opening(&mut rng)).collect();
+    let games: u32 = settings.check_openings * 2;
+
+    let points: f64 = openings.iter().map(|opening| play_pair(&mut engine(judged_by(tuned), settings.depth), &mut engine(judged_by(start), settings.depth), opening)).sum();
+    writeln!(out, "Tuned weights scored {} / {} against the starting weights", points, games)?;
+
+    if let Some(budget) = settings.baseline {
+        for (label, weights) in [("Starting", start), ("Tuned", tuned)] {
+            let mut baseline: MctsPlayer = MctsPlayer::new(budget, TieBreak::Seeded(settings.seed));
+            let points: f64 = openings.iter().map(|opening| play_pair(&mut engine(judged_by(weights), settings.depth), &mut baseline, opening)).sum();
+            writeln!(out, "{} weights scored {} / {} against Monte Carlo search (budget {})", label, points, games, budget)?;
+        }
+    }
+
+    // the same weights on both sides, so only the evaluators differ
+    if let Some(rival) = settings.rival {
+        let mut rival_engine: MinimaxPlayer = engine(Evaluator { kind: rival, weights: tuned }, settings.depth);
+        let points: f64 = openings.iter().map(|opening| play_pair(&mut engine(judged_by(tuned), settings.depth), &mut rival_engine, opening)).sum();
+        writeln!(out, "Tuned weights scored {} / {} judged by {} against the same weights judged by {}", points, games, settings.evaluator, rival)?;
+    }
+
+    let header: String = format!(
+        "# connect_4 evaluation weights, tuned by self-play ({} iterations at depth {}, seed {})\n# load with `connect_4 --weights {}`, or copy them under [ai.weights] in the config file\n",
+        settings.iterations, settings.depth, settings.seed, settings.out.display());
+    tuned.save(&settings.out, &header)?;
+    writeln!(out, "Wrote {}", settings.out.display())?;
+
+    return Ok(tuned);
+}
+
+/**
+ Plays two games between the same players from the same opening, one with each colour, returning `first`'s points (1 a win, 0.5 a draw)
+ * `first` - the player being scored
+ * `second` - the player they're up against
+ * `opening` - moves played before the players take over
+ */
+pub fn play_pair(first: &mut dyn Player, second: &mut dyn Player, opening: &[Column]) -> f64 {
+    let as_red: f64 = play_game(first, second, opening);
+    let as_yellow: f64 = 1.0 - play_game(second, first, opening);
+
+    return as_red + as_yellow;
+}
+
+/**
+ Plays one game between two engines after an opening, returning red's points (1 a win, 0.5 a draw, 0 a loss)
+ * `red` - the player who moves first
+ * `yellow` - the player who moves second
+ * `opening` - moves played before the players take over
+ */
+pub fn play_game(red: &mut dyn Player, yellow: &mut dyn Player, opening: &[Column]) -> f64 {
+    let mut game: GameState = GameState::new();
+    for &col in opening { game.play(col).expect("openings are short enough that every column is open"); }
+
+    while game.status() == GameStatus::InProgress {
+        let player: &mut dyn Player = if game.current_piece() == board::RED_PIECE { &mut *red } else { &mut *yellow };
+        let column: Column = player.choose_move(&game).expect("engines always find a move");
+        game.play(column).expect("engines only pick open columns");
+    }
+
+    return match game.status() {
+        GameStatus::Won(piece) if piece == board::RED_PIECE => 1.0,
+        GameStatus::Won(_) => 0.0,
+        _ => 0.5,
+    };
+}
+
+/**
+ A minimax engine judging positions with the given evaluator; deterministic, so the only randomness in self-play is the opening
+ * `evaluator` - how positions are judged, and what each pattern is worth
+ * `depth` - how far it searches
+ */
+fn engine(evaluator: Evaluator, depth: u16) -> MinimaxPlayer {
+    return MinimaxPlayer::with_tie_break(depth, TieBreak::Deterministic).with_evaluator(evaluator);
+}
+
+/**
+ A few random moves to start a game from
+ * `rng` - where the moves come from
+ */
+fn random_opening(rng: &mut fastrand::Rng) -> Vec<Column> {
+    let plies: usize = rng.usize(OPENING_PLIES);
+
+    return (0..plies).map(|_| rng.usize(0..board::BOARD_WIDTH)).collect();
+}
+
+/**
+ Rounds tuned values into weights, keeping `four` from the starting weights
+ * `start` - the weights tuning began from
+ * `theta` - three, two, opponent_three and center
+ */
+fn weights_at(start: &EvalWeights, theta: [f64; 4]) -> EvalWeights {
+    let round = |value: f64| value.round() as i16;
+
+    return EvalWeights { four: start.four, three: round(theta[0]), two: round(theta[1]), opponent_three: round(theta[2]), center: round(theta[3]) };
+}
+
+/**
+ Weights as shown in progress reports
+ * `weights` - the weights to show
+ */
+fn describe(weights: &EvalWeights) -> String {
+    return format!("three {}, two {}, opponent_three {}, center {}", weights.three, weights.two, weights.opponent_three, weights.center);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    use crate::eval::MAX_WEIGHT;
+
+    #[test]
+    fn identical_engines_split_every_pair() {
+        for opening in [vec![], vec![3, 3], vec![0, 6, 2]] {
+            let points: f64 = play_pair(&mut engine(Evaluator::default(), 2), &mut engine(Evaluator::default(), 2), &opening);
+
+            assert_eq!(points, 1.0, "opening {:?}", opening);
+        }
+    }
+
+    #[test]
+    fn weights_survive_saving_and_loading() {
+        let path: PathBuf = std::env::temp_dir().join(format!("connect_4_weights_{}.toml", std::process::id()));
+        let weights: EvalWeights = EvalWeights { four: 120, three: 7, two: -1, opponent_three: -9, center: MAX_WEIGHT };
+
+        weights.save(&path, "# tuned in a test\n").unwrap();
+        let loaded: Result<EvalWeights, String> = EvalWeights::load(&path);
+
+        std::fs::write(&path, format!("three = {}\n", -MAX_WEIGHT - 1)).unwrap();
+        let too_small: Result<EvalWeights, String> = EvalWeights::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, Ok(weights));
+        assert!(too_small.unwrap_err().contains("weights must be from -1000 to 1000"));
+    }
+
+    #[test]
+    fn threats_can_be_tuned_and_played_against_patterns() {
+        let out: PathBuf = std::env::temp_dir().join(format!("connect_4_tuned_threats_{}.toml", std::process::id()));
+        let settings: TuneSettings = TuneSettings { iterations: 1, depth: 1, check_openings: 2, seed: 5, out: out.clone(), evaluator: EvaluatorKind::Threats, rival: Some(EvaluatorKind::Patterns), ..TuneSettings::default() };
+
+        let mut report: Vec<u8> = Vec::new();
+        let tuned: EvalWeights = tune(&settings, &mut report).unwrap();
+        std::fs::remove_file(&out).unwrap();
+
+        let report: String = String::from_utf8(report).unwrap();
+        assert!(report.starts_with("Tuning threats from"), "{}", report);
+        assert!(report.contains("/ 4 judged by threats against the same weights judged by patterns"), "{}", report);
+        assert_eq!(tuned.four, settings.start.four);
+    }
+}
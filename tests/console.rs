@@ -0,0 +1,92 @@
+// explicit `return` statements are the house style
+#![allow(clippy::needless_return)]
+
+use connect_4::ai_opponent::TieBreak;
+use connect_4::config::Config;
+use connect_4::console::{Console, Options};
+use connect_4::error::GameError;
+use connect_4::input::ReaderInput;
+
+/**
+ Plays a scripted console session until the script runs out, returning everything written
+ * `options` - command line settings for the session
+ * `script` - the lines typed, in order
+ */
+fn play_session(options: &Options, script: &[&str]) -> String {
+    // consoles off the terminal have no typewriter effects or pauses; `colored` has one switch for the whole process,
+    // which every test turns off, so the text checked is plain
+    colored::control::set_override(false);
+
+    let input: String = script.iter().map(|line| format!("{}\n", line)).collect();
+    let console: Console<ReaderInput<&[u8]>, Vec<u8>> = Console::new(ReaderInput::new(input.as_bytes()), Vec::new());
+    let mut config: Config = Config::default();
+
+    // a session goes on game after game, so it only ends when the input does
+    let result: Result<(), GameError> = console.play(options, &mut config, None);
+    assert!(matches!(result, Err(GameError::InputClosed)), "session ended with {:?}", result);
+
+    return String::from_utf8(console.into_output()).unwrap();
+}
+
+#[test]
+fn pvp_game_is_won_by_four_in_a_column() {
+    let output: String = play_session(&Options::default(), &[
+        // no title card, no AI
+        "n", "n",
+        "A", "B", "A", "B", "A", "B", "A",
+        // don't save or review the game
+        "", "n",
+    ]);
+
+    assert!(output.contains("Player 1 wins!"));
+    assert!(!output.contains("Player 2 wins!"));
+    // back to choosing a mode for the next game
+    assert_eq!(output.matches("Play vs AI").count(), 2);
+}
+
+#[test]
+fn pvp_game_fills_the_board_without_a_winner() {
+    let moves: &str = "F E F A G C E F F A E B B A E F G F D B B C C G C G G D G C A D A D D E D B E C B A";
+    let mut script: Vec<&str> = vec!["n", "n"];
+    script.extend(moves.split(' '));
+    script.extend(["", "n"]);
+
+    let output: String = play_session(&Options::default(), &script);
+
+    assert!(output.contains("Tie! No one wins!"));
+    assert!(!output.contains("Player 1 wins!") && !output.contains("Player 2 wins!"));
+}
+
+#[test]
+fn invalid_and_undone_moves_are_played_again() {
+    let output: String = play_session(&Options::default(), &[
+        "n", "n",
+        // Z isn't a column, then Player 1 takes back their A (and Player 2's B after it)
+        "Z", "A", "B", "undo",
+        "C", "D", "C", "D", "C", "D", "C",
+        "", "n",
+    ]);
+
+    assert!(output.contains("Invalid input!"));
+    assert!(output.contains("Player 1 wins!"));
+}
+
+#[test]
+fn ai_game_with_a_fixed_seed_plays_out_the_same_every_time() {
+    let options: Options = Options { tie_break: Some(TieBreak::Seeded(4)), ..Options::default() };
+    let script: [&str; 11] = [
+        // no title card, play the AI at depth 2
+        "n", "y", "2",
+        // the AI's replies are fixed by the seed, and it connects four after Player 1's sixth move
+        "A", "A", "A", "B", "B", "B",
+        "", "n",
+    ];
+
+    let first: String = play_session(&options, &script);
+    let second: String = play_session(&options, &script);
+
+    assert_eq!(first, second);
+    assert!(first.contains("Thinking..."));
+    assert!(first.contains("Player 2 wins!"));
+    assert_eq!(first.matches("Play vs AI").count(), 2);
+}